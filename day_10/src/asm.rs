@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fmt::Write;
+use crate::isa::{Op, Operand, Program, Reg};
+
+/// Assembly error, with 1-based line and column.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AsmError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// A word of the source, with its line and column.
+#[derive(Clone, Copy)]
+struct Token<'a> {
+    line: usize,
+    column: usize,
+    text: &'a str,
+}
+
+impl Token<'_> {
+    fn error(&self, message: String) -> AsmError {
+        AsmError { line: self.line, column: self.column, message }
+    }
+}
+
+/// Assembles a program with one instruction per line, operands separated by commas, `;` starting
+/// comments and `name:` defining labels, either alone or before an instruction:
+///
+/// ```text
+/// loop:   addx 1
+///         sub a, 1
+///         jnz a, loop ; until a is 0
+/// ```
+///
+/// The puzzle's `noop` and `addx` programs are valid sources.
+pub fn assemble(source: &str) -> Result<Program, AsmError> {
+    let mut labels: BTreeMap<String, usize> = BTreeMap::new();
+    let mut label_lines: HashMap<&str, usize> = HashMap::new();
+    let mut statements: Vec<Vec<Token>> = vec![];
+    for (line_idx, line) in source.lines().enumerate() {
+        let mut tokens = tokenize(line_idx + 1, line);
+        if let Some(label) = tokens.first().and_then(|token| token.text.strip_suffix(':')) {
+            let token = tokens.remove(0);
+            if !is_identifier(label) {
+                return Err(token.error(format!("Invalid label name '{}'", label)));
+            }
+            if let Some(first) = label_lines.insert(label, token.line) {
+                return Err(token.error(format!("Duplicate label '{}', first defined on line {}", label, first)));
+            }
+            labels.insert(label.to_owned(), statements.len());
+        }
+        if !tokens.is_empty() {
+            statements.push(tokens);
+        }
+    }
+    let ops = statements
+        .iter()
+        .map(|tokens| parse_op(&tokens[0], &tokens[1..], &labels))
+        .collect::<Result<_, _>>()?;
+    Ok(Program { ops, labels })
+}
+
+fn tokenize(line: usize, text: &str) -> Vec<Token<'_>> {
+    let code = text.split(';').next().unwrap_or("");
+    let mut tokens = vec![];
+    let mut start = None;
+    for (idx, c) in code.char_indices().chain(std::iter::once((code.len(), ' '))) {
+        let separator = c.is_whitespace() || c == ',';
+        match start {
+            Some(from) if separator => {
+                tokens.push(Token { line, column: code[..from].chars().count() + 1, text: &code[from..idx] });
+                start = None;
+            }
+            None if !separator => start = Some(idx),
+            _ => {}
+        }
+    }
+    tokens
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_op(mnemonic: &Token, operands: &[Token], labels: &BTreeMap<String, usize>) -> Result<Op, AsmError> {
+    let expect = |count: usize| {
+        if operands.len() == count {
+            Ok(())
+        } else {
+            Err(mnemonic.error(format!(
+                "'{}' takes {} operand(s), found {}",
+                mnemonic.text,
+                count,
+                operands.len()
+            )))
+        }
+    };
+    let reg = |token: &Token| {
+        Reg::from_name(token.text).ok_or_else(|| token.error(format!("Unknown register '{}'", token.text)))
+    };
+    let imm = |token: &Token| {
+        token.text.parse::<i64>().map_err(|_| token.error(format!("Invalid number '{}'", token.text)))
+    };
+    let operand = |token: &Token| match Reg::from_name(token.text) {
+        Some(reg) => Ok(Operand::Reg(reg)),
+        None => imm(token).map(Operand::Imm),
+    };
+    let label = |token: &Token| {
+        labels.get(token.text).copied().ok_or_else(|| token.error(format!("Unknown label '{}'", token.text)))
+    };
+
+    match mnemonic.text {
+        "noop" | "ret" | "halt" => {
+            expect(0)?;
+            Ok(match mnemonic.text {
+                "noop" => Op::Noop,
+                "ret" => Op::Ret,
+                _ => Op::Halt,
+            })
+        }
+        "addx" => {
+            expect(1)?;
+            Ok(Op::Addx(imm(&operands[0])?))
+        }
+        "mov" | "add" | "sub" | "mul" => {
+            expect(2)?;
+            let (reg, operand) = (reg(&operands[0])?, operand(&operands[1])?);
+            Ok(match mnemonic.text {
+                "mov" => Op::Mov(reg, operand),
+                "add" => Op::Add(reg, operand),
+                "sub" => Op::Sub(reg, operand),
+                _ => Op::Mul(reg, operand),
+            })
+        }
+        "jmp" | "call" => {
+            expect(1)?;
+            let target = label(&operands[0])?;
+            Ok(if mnemonic.text == "jmp" { Op::Jmp(target) } else { Op::Call(target) })
+        }
+        "jz" | "jnz" => {
+            expect(2)?;
+            let (reg, target) = (reg(&operands[0])?, label(&operands[1])?);
+            Ok(if mnemonic.text == "jz" { Op::Jz(reg, target) } else { Op::Jnz(reg, target) })
+        }
+        _ => Err(mnemonic.error(format!("Unknown instruction '{}'", mnemonic.text))),
+    }
+}
+
+/// Source which assembles back to the same instructions. Targets without a label get a generated
+/// one.
+pub fn disassemble(program: &Program) -> String {
+    let mut names: BTreeMap<usize, String> = BTreeMap::new();
+    for (name, target) in &program.labels {
+        names.entry(*target).or_insert_with(|| name.clone());
+    }
+    for target in program.ops.iter().filter_map(Op::target) {
+        names.entry(target).or_insert_with(|| {
+            let mut name = format!("l{}", target);
+            while program.labels.contains_key(&name) {
+                name.insert(0, '_');
+            }
+            name
+        });
+    }
+
+    let mut out = String::new();
+    for idx in 0..=program.ops.len() {
+        if let Some(name) = names.get(&idx) {
+            writeln!(out, "{}:", name).unwrap();
+        }
+        if let Some(op) = program.ops.get(idx) {
+            writeln!(out, "    {}", format_op(op, &names)).unwrap();
+        }
+    }
+    out
+}
+
+fn format_op(op: &Op, names: &BTreeMap<usize, String>) -> String {
+    let operand = |operand: &Operand| match operand {
+        Operand::Reg(reg) => reg.name().to_owned(),
+        Operand::Imm(v) => v.to_string(),
+    };
+    let name = |target: &usize| names[target].as_str();
+    match op {
+        Op::Noop => "noop".to_owned(),
+        Op::Addx(v) => format!("addx {}", v),
+        Op::Mov(reg, src) => format!("mov {}, {}", reg.name(), operand(src)),
+        Op::Add(reg, src) => format!("add {}, {}", reg.name(), operand(src)),
+        Op::Sub(reg, src) => format!("sub {}, {}", reg.name(), operand(src)),
+        Op::Mul(reg, src) => format!("mul {}, {}", reg.name(), operand(src)),
+        Op::Jmp(target) => format!("jmp {}", name(target)),
+        Op::Jz(reg, target) => format!("jz {}, {}", reg.name(), name(target)),
+        Op::Jnz(reg, target) => format!("jnz {}, {}", reg.name(), name(target)),
+        Op::Call(target) => format!("call {}", name(target)),
+        Op::Ret => "ret".to_owned(),
+        Op::Halt => "halt".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isa::Machine;
+
+    static TEST_PROGRAM: &str = "; Sets x to 2 * 3 with a subroutine.
+        mov a, 3
+        call double
+        halt
+double: ; adds a twice to x
+        mov x, 0
+loop:   add x, 2
+        sub a, 1
+        jnz a, loop
+        ret";
+
+    #[test]
+    fn test_assemble() {
+        let program = assemble(TEST_PROGRAM).unwrap();
+        assert_eq!(program.labels, BTreeMap::from([("double".to_owned(), 3), ("loop".to_owned(), 4)]));
+        assert_eq!(program.ops[1], Op::Call(3));
+        assert_eq!(program.ops[6], Op::Jnz(Reg::A, 4));
+
+        let mut machine = Machine::new();
+        machine.run(&program, 1000, &mut |_, _| {}).unwrap();
+        assert_eq!(machine.cpu.registers.x, 6);
+        // mov, call, 3 × (add, sub, jnz), ret, halt.
+        assert_eq!(machine.cpu.clock, 1 + 2 + 1 + 3 * 5 + 1 + 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source: &str| assemble(source).unwrap_err();
+        assert_eq!(
+            error("noop\n  jmp nowhere"),
+            AsmError { line: 2, column: 7, message: "Unknown label 'nowhere'".to_owned() }
+        );
+        assert_eq!(error("mov q, 1").column, 5);
+        assert_eq!(error("add a,  b2").message, "Invalid number 'b2'");
+        assert_eq!(error("addx").message, "'addx' takes 1 operand(s), found 0");
+        assert_eq!(error("fly a").message, "Unknown instruction 'fly'");
+        assert_eq!(error("a:\nb: noop\na: noop").message, "Duplicate label 'a', first defined on line 1");
+        assert_eq!(error("1a: noop").to_string(), "line 1, column 1: Invalid label name '1a'");
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let program = assemble(TEST_PROGRAM).unwrap();
+        let source = disassemble(&program);
+        assert!(source.starts_with("    mov a, 3\n    call double\n    halt\ndouble:\n    mov x, 0\nloop:\n"));
+        assert_eq!(assemble(&source).unwrap(), program);
+
+        let unlabeled = Program { ops: vec![Op::Jz(Reg::B, 2), Op::Noop], labels: BTreeMap::new() };
+        assert_eq!(disassemble(&unlabeled), "    jz b, l2\n    noop\nl2:\n");
+    }
+}
@@ -0,0 +1,54 @@
+use crate::Instruction;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Registers {
+    pub x: i64,
+    /// General purpose registers of the extended ISA, unused by the puzzle's instructions.
+    pub a: i64,
+    pub b: i64,
+    pub c: i64,
+    pub d: i64,
+}
+
+/// Cycle-accurate CPU: the observer is called once per cycle, with the number of the cycle (starting
+/// at 1) and the registers as they are during that cycle.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cpu {
+    /// Number of cycles completed so far.
+    pub clock: u64,
+    pub registers: Registers,
+}
+
+impl Cpu {
+    pub fn new() -> Cpu {
+        Cpu { clock: 0, registers: Registers { x: 1, a: 0, b: 0, c: 0, d: 0 } }
+    }
+
+    pub fn run(&mut self, instructions: &[Instruction], observer: &mut impl FnMut(u64, &Registers)) {
+        for instruction in instructions {
+            self.execute(instruction, observer);
+        }
+    }
+
+    pub fn execute(&mut self, instruction: &Instruction, observer: &mut impl FnMut(u64, &Registers)) {
+        match instruction {
+            Instruction::Noop => self.tick(observer),
+            Instruction::Addx(v) => {
+                self.tick(observer);
+                self.tick(observer);
+                self.registers.x += v;
+            }
+        }
+    }
+
+    pub fn tick(&mut self, observer: &mut impl FnMut(u64, &Registers)) {
+        self.clock += 1;
+        observer(self.clock, &self.registers);
+    }
+}
+
+impl Default for Cpu {
+    fn default() -> Self {
+        Cpu::new()
+    }
+}
@@ -0,0 +1,34 @@
+pub const WIDTH: usize = 40;
+pub const HEIGHT: usize = 6;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Crt {
+    pub pixels: [[bool; WIDTH]; HEIGHT],
+}
+
+impl Crt {
+    pub fn new() -> Crt {
+        Crt { pixels: [[false; WIDTH]; HEIGHT] }
+    }
+
+    /// Draws the pixel for the given cycle (starting at 1), which is lit if the 3 pixels wide
+    /// sprite centered on `sprite_x` covers it.
+    pub fn draw(&mut self, cycle: u64, sprite_x: i64) {
+        let position = (cycle - 1) as usize % (WIDTH * HEIGHT);
+        let (row, column) = (position / WIDTH, position % WIDTH);
+        self.pixels[row][column] = (sprite_x - column as i64).abs() <= 1;
+    }
+
+    pub fn render(&self) -> String {
+        self.pixels.iter()
+            .map(|row| row.iter().map(|lit| if *lit { '#' } else { '.' }).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Default for Crt {
+    fn default() -> Self {
+        Crt::new()
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::cpu::Registers;
+use crate::isa::{ExecError, Machine, Program, Reg};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Breakpoint {
+    /// Hit by the instruction running during this cycle.
+    Cycle(u64),
+    /// Hit by the instruction setting this register to this value.
+    Register(Reg, i64),
+}
+
+/// Written `cycle=12` or `a=0`.
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Cycle(cycle) => write!(f, "cycle={}", cycle),
+            Breakpoint::Register(reg, value) => write!(f, "{}={}", reg.name(), value),
+        }
+    }
+}
+
+impl FromStr for Breakpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Breakpoint, String> {
+        let invalid = || format!("Invalid breakpoint '{}', expected cycle=<n> or <register>=<value>", s);
+        let (name, value) = s.split_once('=').ok_or_else(invalid)?;
+        match (name, Reg::from_name(name)) {
+            ("cycle", _) => value.parse().map(Breakpoint::Cycle).map_err(|_| invalid()),
+            (_, Some(reg)) => value.parse().map(|value| Breakpoint::Register(reg, value)).map_err(|_| invalid()),
+            (_, None) => Err(invalid()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Stop {
+    Breakpoint(Breakpoint),
+    Halted,
+}
+
+/// Runs a program one instruction at a time. Instructions cannot be interrupted, so execution stops
+/// after the instruction which hit a breakpoint.
+pub struct Debugger<'a> {
+    pub machine: Machine,
+    program: &'a Program,
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(program: &'a Program) -> Debugger<'a> {
+        Debugger { machine: Machine::new(), program, breakpoints: vec![] }
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        let len = self.breakpoints.len();
+        self.breakpoints.retain(|bp| *bp != breakpoint);
+        self.breakpoints.len() < len
+    }
+
+    /// Executes one instruction, and tells whether execution should stop there.
+    pub fn step(&mut self, observer: &mut impl FnMut(u64, &Registers)) -> Result<Option<Stop>, ExecError> {
+        let clock_before = self.machine.cpu.clock;
+        let registers_before = self.machine.cpu.registers;
+        if !self.machine.step(self.program, observer)? {
+            return Ok(Some(Stop::Halted));
+        }
+        let clock_after = self.machine.cpu.clock;
+        let registers_after = self.machine.cpu.registers;
+        let hit = self.breakpoints.iter().find(|breakpoint| match breakpoint {
+            Breakpoint::Cycle(cycle) => clock_before < *cycle && *cycle <= clock_after,
+            Breakpoint::Register(reg, value) => {
+                registers_before.get(*reg) != *value && registers_after.get(*reg) == *value
+            }
+        });
+        Ok(hit.map(|breakpoint| Stop::Breakpoint(*breakpoint)))
+    }
+
+    /// Executes instructions until a breakpoint is hit or the program halts, failing if this takes
+    /// more than `max_cycles`.
+    pub fn resume(&mut self, max_cycles: u64, observer: &mut impl FnMut(u64, &Registers)) -> Result<Stop, ExecError> {
+        let limit = self.machine.cpu.clock + max_cycles;
+        loop {
+            if let Some(stop) = self.step(observer)? {
+                return Ok(stop);
+            }
+            if self.machine.cpu.clock > limit {
+                return Err(ExecError::CycleLimit { limit: max_cycles });
+            }
+        }
+    }
+
+    /// Current position and registers, as `cycle 12, pc 4 (loop+1): x=1 a=0 b=0 c=0 d=0`.
+    pub fn status(&self) -> String {
+        let pc = self.machine.pc;
+        let location = self.program.labels
+            .iter()
+            .filter(|(_, target)| **target <= pc)
+            .max_by_key(|(_, target)| **target)
+            .map(|(name, target)| match pc - target {
+                0 => format!(" ({})", name),
+                offset => format!(" ({}+{})", name, offset),
+            })
+            .unwrap_or_default();
+        format!("cycle {}, pc {}{}: {}", self.machine.cpu.clock, pc, location, self.machine.cpu.registers)
+    }
+
+    /// Runs one command of a debugging session (`break <breakpoint>`, `delete <breakpoint>`,
+    /// `step`, `continue` or `status`), and describes its outcome. `continue` fails if the program
+    /// neither stops nor halts within `max_cycles`.
+    pub fn execute(&mut self, command: &str, max_cycles: u64) -> Result<String, String> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let stop = match words.as_slice() {
+            ["break", breakpoint] => {
+                let breakpoint = breakpoint.parse()?;
+                self.add_breakpoint(breakpoint);
+                return Ok(format!("Breakpoint at {}", breakpoint));
+            }
+            ["delete", breakpoint] => {
+                let breakpoint = breakpoint.parse()?;
+                return if self.remove_breakpoint(breakpoint) {
+                    Ok(format!("Deleted breakpoint at {}", breakpoint))
+                } else {
+                    Err(format!("No breakpoint at {}", breakpoint))
+                };
+            }
+            ["step"] => self.step(&mut |_, _| {}).map_err(|e| e.to_string())?,
+            ["continue"] => Some(self.resume(max_cycles, &mut |_, _| {}).map_err(|e| e.to_string())?),
+            ["status"] => None,
+            _ => return Err(format!("Unknown command '{}'", command.trim())),
+        };
+        Ok(match stop {
+            Some(Stop::Breakpoint(breakpoint)) => format!("Hit {}, {}", breakpoint, self.status()),
+            Some(Stop::Halted) => format!("Halted, {}", self.status()),
+            None => self.status(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::assemble;
+
+    fn program() -> Program {
+        assemble("        mov a, 4
+loop:   addx 3
+        sub a, 1
+        jnz a, loop").unwrap()
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let program = program();
+        let mut debugger = Debugger::new(&program);
+        debugger.add_breakpoint(Breakpoint::Cycle(7));
+        debugger.add_breakpoint(Breakpoint::Register(Reg::X, 10));
+        let mut cycles = vec![];
+        let mut observer = |cycle, _: &Registers| cycles.push(cycle);
+
+        // Cycle 7 is the first cycle of the second `addx`.
+        assert_eq!(debugger.resume(100, &mut observer), Ok(Stop::Breakpoint(Breakpoint::Cycle(7))));
+        assert_eq!(debugger.status(), "cycle 8, pc 2 (loop+1): x=7 a=3 b=0 c=0 d=0");
+        assert_eq!(debugger.resume(100, &mut observer), Ok(Stop::Breakpoint(Breakpoint::Register(Reg::X, 10))));
+        assert_eq!(debugger.machine.cpu.registers.a, 2);
+        assert!(debugger.remove_breakpoint(Breakpoint::Cycle(7)));
+        assert!(!debugger.remove_breakpoint(Breakpoint::Cycle(7)));
+        assert_eq!(debugger.resume(100, &mut observer), Ok(Stop::Halted));
+        assert_eq!(debugger.machine.cpu.registers.x, 13);
+        assert_eq!(cycles, (1..=21).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn test_step_and_limit() {
+        let program = program();
+        let mut debugger = Debugger::new(&program);
+        assert_eq!(debugger.step(&mut |_, _| {}), Ok(None));
+        assert_eq!(debugger.status(), "cycle 1, pc 1 (loop): x=1 a=4 b=0 c=0 d=0");
+        assert_eq!(debugger.resume(5, &mut |_, _| {}), Err(ExecError::CycleLimit { limit: 5 }));
+    }
+
+    #[test]
+    fn test_parse_breakpoint() {
+        assert_eq!("cycle=7".parse(), Ok(Breakpoint::Cycle(7)));
+        assert_eq!("a=-2".parse(), Ok(Breakpoint::Register(Reg::A, -2)));
+        assert_eq!(Breakpoint::Register(Reg::X, 10).to_string(), "x=10");
+        assert!("e=1".parse::<Breakpoint>().is_err());
+        assert!("cycle".parse::<Breakpoint>().is_err());
+        assert!("cycle=-1".parse::<Breakpoint>().is_err());
+    }
+
+    #[test]
+    fn test_session() {
+        let program = program();
+        let mut debugger = Debugger::new(&program);
+        assert_eq!(debugger.execute("break a=2", 100), Ok("Breakpoint at a=2".to_owned()));
+        assert_eq!(debugger.execute("step", 100), Ok("cycle 1, pc 1 (loop): x=1 a=4 b=0 c=0 d=0".to_owned()));
+        assert_eq!(debugger.execute("continue", 100), Ok("Hit a=2, cycle 10, pc 3 (loop+2): x=7 a=2 b=0 c=0 d=0".to_owned()));
+        assert_eq!(debugger.execute("delete a=2", 100), Ok("Deleted breakpoint at a=2".to_owned()));
+        assert!(debugger.execute("delete a=2", 100).is_err());
+        assert!(debugger.execute("jump", 100).is_err());
+        assert_eq!(debugger.execute("continue", 100), Ok("Halted, cycle 21, pc 4 (loop+3): x=13 a=0 b=0 c=0 d=0".to_owned()));
+        assert_eq!(debugger.execute("continue", 100), Ok("Halted, cycle 21, pc 4 (loop+3): x=13 a=0 b=0 c=0 d=0".to_owned()));
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use crate::cpu::{Cpu, Registers};
+use crate::Instruction;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Reg {
+    X,
+    A,
+    B,
+    C,
+    D,
+}
+
+impl Reg {
+    pub const ALL: [Reg; 5] = [Reg::X, Reg::A, Reg::B, Reg::C, Reg::D];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Reg::X => "x",
+            Reg::A => "a",
+            Reg::B => "b",
+            Reg::C => "c",
+            Reg::D => "d",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Reg> {
+        Reg::ALL.into_iter().find(|reg| reg.name() == name)
+    }
+}
+
+impl Registers {
+    pub fn get(&self, reg: Reg) -> i64 {
+        match reg {
+            Reg::X => self.x,
+            Reg::A => self.a,
+            Reg::B => self.b,
+            Reg::C => self.c,
+            Reg::D => self.d,
+        }
+    }
+
+    pub fn get_mut(&mut self, reg: Reg) -> &mut i64 {
+        match reg {
+            Reg::X => &mut self.x,
+            Reg::A => &mut self.a,
+            Reg::B => &mut self.b,
+            Reg::C => &mut self.c,
+            Reg::D => &mut self.d,
+        }
+    }
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = Reg::ALL.iter().map(|reg| format!("{}={}", reg.name(), self.get(*reg))).collect();
+        write!(f, "{}", values.join(" "))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operand {
+    Reg(Reg),
+    Imm(i64),
+}
+
+/// Instructions of the extended ISA. Like `addx`, every instruction takes effect at the end of its
+/// last cycle. Jump and call targets are instruction indices, the length of the program meaning its
+/// end.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Op {
+    /// 1 cycle.
+    Noop,
+    /// Adds to `x`, in 2 cycles.
+    Addx(i64),
+    /// 1 cycle.
+    Mov(Reg, Operand),
+    /// 2 cycles.
+    Add(Reg, Operand),
+    /// 2 cycles.
+    Sub(Reg, Operand),
+    /// 2 cycles.
+    Mul(Reg, Operand),
+    /// 1 cycle.
+    Jmp(usize),
+    /// Jumps if the register is zero, in 1 cycle.
+    Jz(Reg, usize),
+    /// Jumps if the register is not zero, in 1 cycle.
+    Jnz(Reg, usize),
+    /// Pushes the return address and jumps, in 2 cycles.
+    Call(usize),
+    /// 1 cycle.
+    Ret,
+    /// Stops the program, in 1 cycle.
+    Halt,
+}
+
+impl Op {
+    pub fn cycles(&self) -> u64 {
+        match self {
+            Op::Addx(_) | Op::Add(..) | Op::Sub(..) | Op::Mul(..) | Op::Call(_) => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn target(&self) -> Option<usize> {
+        match self {
+            Op::Jmp(target) | Op::Jz(_, target) | Op::Jnz(_, target) | Op::Call(target) => Some(*target),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Instruction> for Op {
+    fn from(instruction: &Instruction) -> Op {
+        match instruction {
+            Instruction::Noop => Op::Noop,
+            Instruction::Addx(v) => Op::Addx(*v),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Program {
+    pub ops: Vec<Op>,
+    /// Index of the instruction following each label.
+    pub labels: BTreeMap<String, usize>,
+}
+
+impl Program {
+    pub fn from_instructions(instructions: &[Instruction]) -> Program {
+        Program { ops: instructions.iter().map(Op::from).collect(), labels: BTreeMap::new() }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExecError {
+    EmptyCallStack { pc: usize },
+    InvalidTarget { pc: usize, target: usize },
+    Overflow { pc: usize },
+    CycleLimit { limit: u64 },
+}
+
+impl fmt::Display for ExecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecError::EmptyCallStack { pc } => write!(f, "ret without call at instruction {}", pc),
+            ExecError::InvalidTarget { pc, target } => {
+                write!(f, "Invalid target {} at instruction {}", target, pc)
+            }
+            ExecError::Overflow { pc } => write!(f, "Overflow at instruction {}", pc),
+            ExecError::CycleLimit { limit } => write!(f, "Still running after {} cycles", limit),
+        }
+    }
+}
+
+impl std::error::Error for ExecError {}
+
+/// A `Cpu` running a program of the extended ISA.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Machine {
+    pub cpu: Cpu,
+    pub pc: usize,
+    pub call_stack: Vec<usize>,
+    pub halted: bool,
+}
+
+impl Machine {
+    pub fn new() -> Machine {
+        Machine { cpu: Cpu::new(), pc: 0, call_stack: vec![], halted: false }
+    }
+
+    /// Executes the next instruction, and returns `false` instead once the program has halted or
+    /// run past its end.
+    pub fn step(&mut self, program: &Program, observer: &mut impl FnMut(u64, &Registers)) -> Result<bool, ExecError> {
+        let pc = self.pc;
+        let op = match program.ops.get(pc) {
+            Some(op) if !self.halted => op,
+            _ => {
+                self.halted = true;
+                return Ok(false);
+            }
+        };
+        if let Some(target) = op.target() {
+            if target > program.ops.len() {
+                return Err(ExecError::InvalidTarget { pc, target });
+            }
+        }
+        for _ in 0..op.cycles() {
+            self.cpu.tick(observer);
+        }
+
+        let registers = &mut self.cpu.registers;
+        let value = |operand: &Operand| match operand {
+            Operand::Reg(reg) => registers.get(*reg),
+            Operand::Imm(v) => *v,
+        };
+        let arithmetic = |reg: &Reg, result: Option<i64>| result.map(|v| (*reg, v)).ok_or(ExecError::Overflow { pc });
+        let mut next = pc + 1;
+        let update = match op {
+            Op::Noop | Op::Halt => None,
+            Op::Addx(v) => Some(arithmetic(&Reg::X, registers.x.checked_add(*v))?),
+            Op::Mov(reg, operand) => Some((*reg, value(operand))),
+            Op::Add(reg, operand) => Some(arithmetic(reg, registers.get(*reg).checked_add(value(operand)))?),
+            Op::Sub(reg, operand) => Some(arithmetic(reg, registers.get(*reg).checked_sub(value(operand)))?),
+            Op::Mul(reg, operand) => Some(arithmetic(reg, registers.get(*reg).checked_mul(value(operand)))?),
+            Op::Jmp(target) => {
+                next = *target;
+                None
+            }
+            Op::Jz(reg, target) => {
+                if registers.get(*reg) == 0 {
+                    next = *target;
+                }
+                None
+            }
+            Op::Jnz(reg, target) => {
+                if registers.get(*reg) != 0 {
+                    next = *target;
+                }
+                None
+            }
+            Op::Call(target) => {
+                self.call_stack.push(next);
+                next = *target;
+                None
+            }
+            Op::Ret => {
+                next = self.call_stack.pop().ok_or(ExecError::EmptyCallStack { pc })?;
+                None
+            }
+        };
+        if let Some((reg, v)) = update {
+            *registers.get_mut(reg) = v;
+        }
+        self.halted = *op == Op::Halt;
+        self.pc = next;
+        Ok(true)
+    }
+
+    /// Runs the program to its end, failing if it takes more than `max_cycles`.
+    pub fn run(
+        &mut self,
+        program: &Program,
+        max_cycles: u64,
+        observer: &mut impl FnMut(u64, &Registers),
+    ) -> Result<(), ExecError> {
+        while self.step(program, observer)? {
+            if self.cpu.clock > max_cycles {
+                return Err(ExecError::CycleLimit { limit: max_cycles });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Machine::new()
+    }
+}
@@ -1,50 +1,111 @@
 use std::error::Error;
+use std::io::{self, BufRead};
+use crate::asm::{assemble, disassemble};
+use crate::cpu::Cpu;
+use crate::crt::Crt;
+use crate::debugger::Debugger;
+use crate::isa::{Machine, Program};
+use crate::ocr::read_letters;
+
+mod asm;
+mod cpu;
+mod crt;
+mod debugger;
+mod isa;
+mod ocr;
 
 static INPUT: &str = include_str!("input");
 
+/// Cycles after which a program given on the command line is considered stuck.
+const MAX_CYCLES: u64 = 1_000_000;
+
+static USAGE: &str = "Usage: day_10 [<file> | disassemble [file] | debug [file] [--break <breakpoint>...]]";
+
 fn main() -> Result<(), Box<dyn Error>> {
-    let instructions = parse_input(INPUT)?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
 
-    println!("Part 1 result: {}", execute(&instructions));
-    // println!("Part 2 result: {}", part_2(&moves));
+    match args.as_slice() {
+        [] => {
+            let instructions = parse_input(INPUT)?;
+            println!("Part 1 result: {}", part_1(&instructions));
+            let crt = part_2(&instructions);
+            match read_letters(&crt) {
+                Ok(letters) => println!("Part 2 result: {}", letters),
+                Err(err) => println!("Part 2 result: {}\n{}", err, crt.render()),
+            }
+        }
+        ["disassemble", source @ ..] => print!("{}", disassemble(&load_program(source)?)),
+        ["debug", options @ ..] => {
+            let (source, breakpoints) = match options.iter().position(|option| *option == "--break") {
+                Some(idx) => options.split_at(idx),
+                None => (options, &[][..]),
+            };
+            let program = load_program(source)?;
+            let mut debugger = Debugger::new(&program);
+            for option in breakpoints.chunks(2) {
+                match option {
+                    ["--break", breakpoint] => debugger.add_breakpoint(breakpoint.parse()?),
+                    _ => Err(USAGE)?,
+                }
+            }
+            debug(&mut debugger)?;
+        }
+        // Runs an assembly file on the CRT instead of solving the puzzle.
+        [path] => {
+            let program = assemble(&std::fs::read_to_string(path)?)?;
+            let mut crt = Crt::new();
+            let mut machine = Machine::new();
+            machine.run(&program, MAX_CYCLES, &mut |cycle, registers| crt.draw(cycle, registers.x))?;
+            println!("{}", crt.render());
+            println!("{} cycles, {}", machine.cpu.clock, machine.cpu.registers);
+        }
+        _ => Err(USAGE)?,
+    }
 
     Ok(())
 }
 
-fn execute(instructions: &[Instruction]) -> i64 {
-    let mut cycle = 0;
-    let mut register = 1;
-    let mut counter = 0;
+/// An assembly file, or the puzzle input when no file is given.
+fn load_program(source: &[&str]) -> Result<Program, Box<dyn Error>> {
+    match source {
+        [] => Ok(Program::from_instructions(&parse_input(INPUT)?)),
+        [path] => Ok(assemble(&std::fs::read_to_string(path)?)?),
+        _ => Err(USAGE)?,
+    }
+}
 
-    for instruction in instructions {
-        match instruction {
-            Instruction::Noop => {
-                step(&mut cycle, register, &mut counter);
-            }
-            Instruction::Addx(v) => {
-                step(&mut cycle, register, &mut counter);
-                step(&mut cycle, register, &mut counter);
-                register += v;
-            }
+/// Reads debugger commands from the standard input, one per line, until `quit` or its end.
+fn debug(debugger: &mut Debugger) -> Result<(), Box<dyn Error>> {
+    println!("{}", debugger.status());
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        match line.trim() {
+            "" => continue,
+            "quit" => break,
+            command => match debugger.execute(command, MAX_CYCLES) {
+                Ok(output) => println!("{}", output),
+                Err(err) => println!("{}", err),
+            },
         }
     }
+    Ok(())
+}
+
+fn part_1(instructions: &[Instruction]) -> i64 {
+    let mut counter = 0;
+    Cpu::new().run(instructions, &mut |cycle, registers| {
+        if (cycle + 20) % 40 == 0 {
+            counter += registers.x * cycle as i64;
+        }
+    });
     counter
 }
 
-fn step(cycle: &mut i64, register: i64, counter: &mut i64) {
-    let beam = *cycle % 40;
-    if beam >= register - 1 && beam <= register + 1 {
-        print!("#");
-    } else {
-        print!(".");
-    }
-    if beam == 39 {
-        println!();
-    }
-    *cycle += 1;
-    if (*cycle + 20) % 40 == 0 {
-        *counter += register * *cycle;
-    }
+fn part_2(instructions: &[Instruction]) -> Crt {
+    let mut crt = Crt::new();
+    Cpu::new().run(instructions, &mut |cycle, registers| crt.draw(cycle, registers.x));
+    crt
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -76,6 +137,8 @@ fn parse_input(input: &str) -> Result<Vec<Instruction>, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::asm::disassemble;
+    use crate::isa::Program;
 
     fn test_instructions() -> Vec<Instruction> {
         let test_input = "addx 15
@@ -245,11 +308,40 @@ addx -5",
 
     #[test]
     fn test_part_1() {
-        assert_eq!(execute(&test_instructions()), 13140);
+        assert_eq!(part_1(&test_instructions()), 13140);
     }
 
-    // #[test]
-    // fn test_part_2() {
-    //     assert_eq!(part_2(&test_moves()), 1);
-    // }
+    #[test]
+    fn test_part_2() {
+        let expected = "##..##..##..##..##..##..##..##..##..##..
+###...###...###...###...###...###...###.
+####....####....####....####....####....
+#####.....#####.....#####.....#####.....
+######......######......######......####
+#######.......#######.......#######.....";
+        assert_eq!(part_2(&test_instructions()).render(), expected);
+    }
+
+    #[test]
+    fn test_cpu_cycles() {
+        let mut states = vec![];
+        let mut cpu = Cpu::new();
+        cpu.run(&[Instruction::Noop, Instruction::Addx(3), Instruction::Addx(-5)], &mut |cycle, registers| {
+            states.push((cycle, registers.x))
+        });
+        assert_eq!(states, vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)]);
+        assert_eq!(cpu.clock, 5);
+        assert_eq!(cpu.registers.x, -1);
+    }
+
+    #[test]
+    fn test_machine_runs_puzzle_programs() {
+        let instructions = test_instructions();
+        let mut crt = Crt::new();
+        let mut machine = Machine::new();
+        let program = assemble(&disassemble(&Program::from_instructions(&instructions))).unwrap();
+        machine.run(&program, 1000, &mut |cycle, registers| crt.draw(cycle, registers.x)).unwrap();
+        assert_eq!(crt, part_2(&instructions));
+        assert_eq!(machine.cpu.clock, 240);
+    }
 }
@@ -0,0 +1,144 @@
+use std::fmt;
+use crate::crt::{Crt, HEIGHT, WIDTH};
+
+const GLYPH_WIDTH: usize = 4;
+/// Each glyph is followed by one blank column.
+const GLYPH_SPACING: usize = GLYPH_WIDTH + 1;
+
+/// The 4×6 font used by the puzzles, one string per glyph with rows separated by `/`.
+const FONT: [(char, &str); 17] = [
+    ('A', ".##./#..#/#..#/####/#..#/#..#"),
+    ('B', "###./#..#/###./#..#/#..#/###."),
+    ('C', ".##./#..#/#.../#.../#..#/.##."),
+    ('E', "####/#.../###./#.../#.../####"),
+    ('F', "####/#.../###./#.../#.../#..."),
+    ('G', ".##./#..#/#.../#.##/#..#/.###"),
+    ('H', "#..#/#..#/####/#..#/#..#/#..#"),
+    ('I', ".###/..#./..#./..#./..#./.###"),
+    ('J', "..##/...#/...#/...#/#..#/.##."),
+    ('K', "#..#/#.#./##../#.#./#.#./#..#"),
+    ('L', "#.../#.../#.../#.../#.../####"),
+    ('O', ".##./#..#/#..#/#..#/#..#/.##."),
+    ('P', "###./#..#/#..#/###./#.../#..."),
+    ('R', "###./#..#/#..#/###./#.#./#..#"),
+    ('S', ".###/#.../#.../.##./...#/###."),
+    ('U', "#..#/#..#/#..#/#..#/#..#/.##."),
+    ('Z', "####/...#/..#./.#../#.../####"),
+];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnknownGlyph {
+    /// Position of the glyph on the screen, starting at 0.
+    pub index: usize,
+    /// Pixels of the glyph, in the same format as `FONT`.
+    pub pattern: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct OcrError {
+    /// The letters read, with `?` for the unknown glyphs.
+    pub partial: String,
+    pub unknown: Vec<UnknownGlyph>,
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown glyphs in '{}':", self.partial)?;
+        for glyph in &self.unknown {
+            write!(f, " #{} {}", glyph.index, glyph.pattern)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// Reads the letters displayed on the screen. Trailing blank glyphs are ignored.
+pub fn read_letters(crt: &Crt) -> Result<String, OcrError> {
+    let patterns: Vec<String> = (0..WIDTH / GLYPH_SPACING)
+        .map(|index| glyph_pattern(crt, index * GLYPH_SPACING))
+        .collect();
+    let blank = ["...."; HEIGHT].join("/");
+    let count = patterns.iter().rposition(|pattern| *pattern != blank).map_or(0, |last| last + 1);
+
+    let mut partial = String::new();
+    let mut unknown = Vec::new();
+    for (index, pattern) in patterns.into_iter().take(count).enumerate() {
+        match FONT.iter().find(|(_, glyph)| *glyph == pattern) {
+            Some((letter, _)) => partial.push(*letter),
+            None => {
+                partial.push('?');
+                unknown.push(UnknownGlyph { index, pattern });
+            }
+        }
+    }
+    if unknown.is_empty() {
+        Ok(partial)
+    } else {
+        Err(OcrError { partial, unknown })
+    }
+}
+
+fn glyph_pattern(crt: &Crt, left: usize) -> String {
+    crt.pixels.iter()
+        .map(|row| {
+            row[left..left + GLYPH_WIDTH].iter().map(|lit| if *lit { '#' } else { '.' }).collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crt(screen: &str) -> Crt {
+        let mut crt = Crt::new();
+        for (y, line) in screen.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                crt.pixels[y][x] = c == '#';
+            }
+        }
+        crt
+    }
+
+    #[test]
+    fn test_read_letters() {
+        let screen = crt("###..####.####.####.#..#.###..####..##..
+#..#.#.......#.#....#.#..#..#.#....#..#.
+#..#.###....#..###..##...###..###..#..#.
+###..#.....#...#....#.#..#..#.#....####.
+#.#..#....#....#....#.#..#..#.#....#..#.
+#..#.#....####.####.#..#.###..#....#..#.");
+        assert_eq!(read_letters(&screen), Ok("RFZEKBFA".to_owned()));
+    }
+
+    #[test]
+    fn test_short_text() {
+        let screen = crt(".##..#..#
+#..#.#..#
+#....####
+#.##.#..#
+#..#.#..#
+.###.#..#");
+        assert_eq!(read_letters(&screen), Ok("GH".to_owned()));
+        assert_eq!(read_letters(&Crt::new()), Ok(String::new()));
+    }
+
+    #[test]
+    fn test_unknown_glyph() {
+        let screen = crt("#.....##.
+#....#..#
+#....#..#
+#....####
+#....#..#
+#....#..#");
+        assert_eq!(
+            read_letters(&screen),
+            Err(OcrError {
+                partial: "?A".to_owned(),
+                unknown: vec![UnknownGlyph { index: 0, pattern: "#.../#.../#.../#.../#.../#...".to_owned() }],
+            })
+        );
+    }
+}
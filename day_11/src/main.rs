@@ -1,189 +1,164 @@
+use crate::models::Monkey;
+use crate::parsers::parse_monkeys;
+use crate::trace::{extrapolated_inspection_counts, ItemTrace};
+use crate::worry::{exact_inspection_counts, WorryRules};
 use common::itertools::Itertools;
-use std::collections::VecDeque;
 use std::error::Error;
 
+mod models;
+mod parsers;
+mod trace;
+mod worry;
+
+static INPUT: &str = include_str!("input");
+
 fn main() -> Result<(), Box<dyn Error>> {
-    println!("Part 1 result: {}", part_1(input()));
-    println!("Part 2 result: {}", part_2(input()));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    let monkeys = parse_monkeys(INPUT)?;
+
+    match args.as_slice() {
+        // `cargo run -p day_11 -- validate 20 1` compares the bounded simulation with exact worry levels.
+        ["validate", rounds, relief] => {
+            let (rounds, relief) = (rounds.parse()?, relief.parse()?);
+            let bounded = inspection_counts(&monkeys, rounds, &WorryRules::new(&monkeys, relief)?)?;
+            let exact = exact_inspection_counts(&monkeys, rounds, relief);
+            println!("Bounded: {:?}", bounded);
+            println!("Exact:   {:?}", exact);
+            if bounded != exact {
+                Err("The bounded simulation differs from the exact one")?;
+            }
+        }
+        // `cargo run -p day_11 -- rounds 1000000000000` gives the monkey business after any number of rounds.
+        ["rounds", rounds] => {
+            let counts = extrapolated_inspection_counts(&monkeys, &WorryRules::new(&monkeys, 1)?, rounds.parse()?)?;
+            let business: u128 = counts.iter().sorted().rev().take(2).map(|c| *c as u128).product();
+            println!("Inspections: {:?}", counts);
+            println!("Monkey business: {}", business);
+        }
+        ["trace", monkey, item] => {
+            let start = (monkey.parse()?, item.parse()?);
+            let trace = ItemTrace::trace(&monkeys, &WorryRules::new(&monkeys, 1)?, start, u64::MAX)?;
+            for throw in &trace.history {
+                println!("Round {}: {} -> {} with {}", throw.round, throw.from, throw.to, throw.worry);
+            }
+            if let Some((start, length)) = trace.cycle {
+                println!("Repeats every {} rounds from round {}", length, start);
+            }
+        }
+        ["trace", monkey, item, round] => {
+            let start = (monkey.parse()?, item.parse()?);
+            let trace = ItemTrace::trace(&monkeys, &WorryRules::new(&monkeys, 1)?, start, u64::MAX)?;
+            for throw in trace.round_throws(round.parse()?).unwrap_or_default() {
+                println!("{} -> {} with {}", throw.from, throw.to, throw.worry);
+            }
+        }
+        [] => {
+            println!("Part 1 result: {}", part_1(&monkeys)?);
+            println!("Part 2 result: {}", part_2(&monkeys)?);
+        }
+        _ => Err("Usage: day_11 [validate <rounds> <relief> | rounds <rounds> | trace <monkey> <worry level> [round]]")?,
+    }
 
     Ok(())
 }
 
-fn part_1(m: Vec<Monkey>) -> usize {
-    let mut monkeys = m;
-    for r in 0..20 {
-        play_round(&mut monkeys, 3);
-    }
-    monkey_business(&monkeys)
+fn part_1(monkeys: &[Monkey]) -> Result<usize, String> {
+    let counts = inspection_counts(monkeys, 20, &WorryRules::new(monkeys, 3)?)?;
+    Ok(monkey_business(&counts))
+}
+
+fn part_2(monkeys: &[Monkey]) -> Result<usize, String> {
+    let counts = inspection_counts(monkeys, 10000, &WorryRules::new(monkeys, 1)?)?;
+    Ok(monkey_business(&counts))
 }
 
-fn part_2(m: Vec<Monkey>) -> usize {
-    let mut monkeys = m;
-    for r in 0..10000 {
-        play_round(&mut monkeys, 1);
+fn inspection_counts(m: &[Monkey], rounds: usize, rules: &WorryRules) -> Result<Vec<usize>, String> {
+    let mut monkeys = m.to_vec();
+    for _ in 0..rounds {
+        play_round(&mut monkeys, rules)?;
     }
-    monkey_business(&monkeys)
+    Ok(monkeys.iter().map(|m| m.inspection_count).collect())
 }
 
-fn monkey_business(monkeys: &[Monkey]) -> usize {
-    monkeys
+fn monkey_business(inspection_counts: &[usize]) -> usize {
+    inspection_counts
         .iter()
-        .map(|m| m.inspection_count)
         .sorted()
         .rev()
         .take(2)
         .product()
 }
 
-fn play_round(monkeys: &mut Vec<Monkey>, divide_by: usize) {
-    // The test divisors are all different primes, so we can just multiply them to get the LCM
-    let lcm: usize = monkeys.iter().map(|m| m.test_divisible_by).product();
+/// Items a monkey throws to itself are kept for the next round.
+fn play_round(monkeys: &mut [Monkey], rules: &WorryRules) -> Result<(), String> {
     for m in 0..monkeys.len() {
-        let items = monkeys[m].items.clone();
+        let items = std::mem::take(&mut monkeys[m].items);
         monkeys[m].inspection_count += items.len();
         for item in items {
-            let new_worry_level = (monkeys[m].operation)(item) / divide_by % lcm;
-            let throw_to: usize = if new_worry_level % monkeys[m].test_divisible_by == 0 {
+            let new_worry_level = rules.inspect(&monkeys[m].operation, item)?;
+            let throw_to: usize = if new_worry_level.is_multiple_of(monkeys[m].test_divisible_by) {
                 monkeys[m].test_dest_true
             } else {
                 monkeys[m].test_dest_false
             };
             monkeys[throw_to].items.push(new_worry_level);
         }
-        monkeys[m].items = vec![];
     }
-    // monkeys
-    //     .iter()
-    //     .enumerate()
-    //     .for_each(|(i, m)| println!("Monkey {} inspected items {} times.", i, m.inspection_count));
-}
-
-struct Monkey {
-    items: Vec<usize>,
-    operation: Box<dyn Fn(usize) -> usize>,
-    test_divisible_by: usize,
-    test_dest_true: usize,
-    test_dest_false: usize,
-    inspection_count: usize,
-}
-
-fn input() -> Vec<Monkey> {
-    vec![
-        Monkey {
-            items: vec![54, 61, 97, 63, 74],
-            operation: Box::new(|old| old * 7),
-            test_divisible_by: 17,
-            test_dest_true: 5,
-            test_dest_false: 3,
-            inspection_count: 0,
-        },
-        Monkey {
-            items: vec![61, 70, 97, 64, 99, 83, 52, 87],
-            operation: Box::new(|old| old + 8),
-            test_divisible_by: 2,
-            test_dest_true: 7,
-            test_dest_false: 6,
-            inspection_count: 0,
-        },
-        Monkey {
-            items: vec![60, 67, 80, 65],
-            operation: Box::new(|old| old * 13),
-            test_divisible_by: 5,
-            test_dest_true: 1,
-            test_dest_false: 6,
-            inspection_count: 0,
-        },
-        Monkey {
-            items: vec![61, 70, 76, 69, 82, 56],
-            operation: Box::new(|old| old + 7),
-            test_divisible_by: 3,
-            test_dest_true: 5,
-            test_dest_false: 2,
-            inspection_count: 0,
-        },
-        Monkey {
-            items: vec![79, 98],
-            operation: Box::new(|old| old + 2),
-            test_divisible_by: 7,
-            test_dest_true: 0,
-            test_dest_false: 3,
-            inspection_count: 0,
-        },
-        Monkey {
-            items: vec![72, 79, 55],
-            operation: Box::new(|old| old + 1),
-            test_divisible_by: 13,
-            test_dest_true: 2,
-            test_dest_false: 1,
-            inspection_count: 0,
-        },
-        Monkey {
-            items: vec![63],
-            operation: Box::new(|old| old + 4),
-            test_divisible_by: 19,
-            test_dest_true: 7,
-            test_dest_false: 4,
-            inspection_count: 0,
-        },
-        Monkey {
-            items: vec![72, 51, 93, 63, 80, 86, 81],
-            operation: Box::new(|old| old * old),
-            test_divisible_by: 11,
-            test_dest_true: 0,
-            test_dest_false: 4,
-            inspection_count: 0,
-        },
-    ]
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn test_monkeys() -> Vec<Monkey> {
-        vec![
-            Monkey {
-                items: vec![79, 98],
-                operation: Box::new(|old| old * 19),
-                test_divisible_by: 23,
-                test_dest_true: 2,
-                test_dest_false: 3,
-                inspection_count: 0,
-            },
-            Monkey {
-                items: vec![54, 65, 75, 74],
-                operation: Box::new(|old| old + 6),
-                test_divisible_by: 19,
-                test_dest_true: 2,
-                test_dest_false: 0,
-                inspection_count: 0,
-            },
-            Monkey {
-                items: vec![79, 60, 97],
-                operation: Box::new(|old| old * old),
-                test_divisible_by: 13,
-                test_dest_true: 1,
-                test_dest_false: 3,
-                inspection_count: 0,
-            },
-            Monkey {
-                items: vec![74],
-                operation: Box::new(|old| old + 3),
-                test_divisible_by: 17,
-                test_dest_true: 0,
-                test_dest_false: 1,
-                inspection_count: 0,
-            },
-        ]
-    }
+    static TEST_INPUT: &str = "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1";
 
     #[test]
     fn test_part_1() {
-        assert_eq!(part_1(test_monkeys()), 10605);
+        assert_eq!(part_1(&parse_monkeys(TEST_INPUT).unwrap()).unwrap(), 10605);
     }
 
     #[test]
     fn test_part_2() {
-        assert_eq!(part_2(test_monkeys()), 2713310158);
+        assert_eq!(part_2(&parse_monkeys(TEST_INPUT).unwrap()).unwrap(), 2713310158);
+    }
+
+    #[test]
+    fn test_throw_to_self() {
+        let monkeys = parse_monkeys("Monkey 0:
+  Starting items: 1, 2
+  Operation: new = old + 1
+  Test: divisible by 2
+    If true: throw to monkey 0
+    If false: throw to monkey 0").unwrap();
+        let rules = WorryRules::new(&monkeys, 1).unwrap();
+        assert_eq!(inspection_counts(&monkeys, 5, &rules), Ok(vec![10]));
     }
 }
@@ -0,0 +1,117 @@
+use num_bigint::BigUint;
+use std::fmt;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Monkey {
+    pub items: Vec<usize>,
+    pub operation: Expr,
+    pub test_divisible_by: usize,
+    pub test_dest_true: usize,
+    pub test_dest_false: usize,
+    pub inspection_count: usize,
+}
+
+/// Right-hand side of a monkey's `new = ...` operation.
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+pub enum Expr {
+    Old,
+    Const(usize),
+    Op(Box<Expr>, Operation, Box<Expr>),
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Operation {
+    Add,
+    Mul,
+}
+
+impl Operation {
+    pub fn checked_apply(&self, v1: usize, v2: usize) -> Option<usize> {
+        match self {
+            Operation::Add => v1.checked_add(v2),
+            Operation::Mul => v1.checked_mul(v2),
+        }
+    }
+
+    /// Applies the operation to values below `modulus`, which cannot overflow.
+    pub fn apply_mod(&self, v1: usize, v2: usize, modulus: usize) -> usize {
+        let (v1, v2) = (v1 as u128, v2 as u128);
+        let result = match self {
+            Operation::Add => v1 + v2,
+            Operation::Mul => v1 * v2,
+        };
+        (result % modulus as u128) as usize
+    }
+
+    pub fn apply_big(&self, v1: BigUint, v2: BigUint) -> BigUint {
+        match self {
+            Operation::Add => v1 + v2,
+            Operation::Mul => v1 * v2,
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Operation::Add => 0,
+            Operation::Mul => 1,
+        }
+    }
+}
+
+impl Expr {
+    /// Exact value, or `None` if it overflows.
+    pub fn checked_eval(&self, old: usize) -> Option<usize> {
+        match self {
+            Expr::Old => Some(old),
+            Expr::Const(n) => Some(*n),
+            Expr::Op(e1, op, e2) => op.checked_apply(e1.checked_eval(old)?, e2.checked_eval(old)?),
+        }
+    }
+
+    /// Value modulo `modulus`, reducing after each operation.
+    pub fn eval_mod(&self, old: usize, modulus: usize) -> usize {
+        match self {
+            Expr::Old => old % modulus,
+            Expr::Const(n) => n % modulus,
+            Expr::Op(e1, op, e2) => op.apply_mod(e1.eval_mod(old, modulus), e2.eval_mod(old, modulus), modulus),
+        }
+    }
+
+    pub fn eval_big(&self, old: &BigUint) -> BigUint {
+        match self {
+            Expr::Old => old.clone(),
+            Expr::Const(n) => BigUint::from(*n),
+            Expr::Op(e1, op, e2) => op.apply_big(e1.eval_big(old), e2.eval_big(old)),
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, parent: Operation) -> fmt::Result {
+        match self {
+            Expr::Op(_, op, _) if op.precedence() < parent.precedence() => write!(f, "({})", self),
+            _ => write!(f, "{}", self),
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Add => write!(f, "+"),
+            Operation::Mul => write!(f, "*"),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Old => write!(f, "old"),
+            Expr::Const(n) => write!(f, "{}", n),
+            Expr::Op(e1, op, e2) => {
+                e1.fmt_operand(f, *op)?;
+                write!(f, " {} ", op)?;
+                e2.fmt_operand(f, *op)
+            }
+        }
+    }
+}
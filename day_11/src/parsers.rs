@@ -0,0 +1,108 @@
+use crate::models::{Expr, Monkey, Operation};
+use peg::error::ParseError;
+use peg::str::LineCol;
+
+pub fn parse_monkeys(input: &str) -> Result<Vec<Monkey>, ParseError<LineCol>> {
+    parser::monkeys(input)
+}
+
+/// Checks that the monkeys are numbered in order from 0 and only throw to existing monkeys.
+fn is_consistent(monkeys: &[(usize, Monkey)]) -> bool {
+    monkeys.iter().enumerate().all(|(idx, (id, monkey))| {
+        *id == idx && monkey.test_dest_true < monkeys.len() && monkey.test_dest_false < monkeys.len()
+    })
+}
+
+peg::parser! {
+    grammar parser() for str {
+        rule _() = [' ']*
+        rule int() -> usize = n:$(['0'..='9']+) {? n.parse().or(Err("integer")) }
+        pub rule expr() -> Expr = precedence!{
+            e1:(@) _ "+" _ e2:@ { Expr::Op(Box::new(e1), Operation::Add, Box::new(e2)) }
+            --
+            e1:(@) _ "*" _ e2:@ { Expr::Op(Box::new(e1), Operation::Mul, Box::new(e2)) }
+            --
+            "old" { Expr::Old }
+            n:int() { Expr::Const(n) }
+            "(" _ e:expr() _ ")" { e }
+        }
+        rule monkey() -> (usize, Monkey) =
+            "Monkey " id:int() ":\n"
+            _ "Starting items:" _ items:(int() ** ("," _)) "\n"
+            _ "Operation: new = " operation:expr() "\n"
+            _ "Test: divisible by " test_divisible_by:int() "\n"
+            _ "If true: throw to monkey " test_dest_true:int() "\n"
+            _ "If false: throw to monkey " test_dest_false:int()
+            {
+                (id, Monkey { items, operation, test_divisible_by, test_dest_true, test_dest_false, inspection_count: 0 })
+            }
+        pub rule monkeys() -> Vec<Monkey> = m:(monkey() ** ("\n"+)) "\n"* {?
+            if is_consistent(&m) {
+                Ok(m.into_iter().map(|(_, monkey)| monkey).collect())
+            } else {
+                Err("monkeys numbered from 0, throwing to existing monkeys")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_monkeys() {
+        let monkeys = parse_monkeys(
+            "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 1
+    If false: throw to monkey 0
+
+Monkey 1:
+  Starting items:
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+",
+        )
+        .unwrap();
+        assert_eq!(
+            monkeys[0],
+            Monkey {
+                items: vec![79, 98],
+                operation: Expr::Op(Box::new(Expr::Old), Operation::Mul, Box::new(Expr::Const(19))),
+                test_divisible_by: 23,
+                test_dest_true: 1,
+                test_dest_false: 0,
+                inspection_count: 0,
+            }
+        );
+        assert!(monkeys[1].items.is_empty());
+        assert_eq!(monkeys[1].operation.to_string(), "old * old");
+    }
+
+    #[test]
+    fn test_expressions() {
+        let expr = parser::expr("old + 2 * (old + 1)").unwrap();
+        assert_eq!(expr.checked_eval(3), Some(11));
+        assert_eq!(expr.eval_mod(3, 7), 4);
+        assert_eq!(expr.to_string(), "old + 2 * (old + 1)");
+        assert_eq!(parser::expr("(old * 2) + 1").unwrap().to_string(), "old * 2 + 1");
+    }
+
+    #[test]
+    fn test_invalid_monkeys() {
+        let monkey = |id: usize, dest: usize| {
+            format!(
+                "Monkey {}:\n  Starting items: 1\n  Operation: new = old\n  Test: divisible by 2\n    If true: throw to monkey {}\n    If false: throw to monkey 0",
+                id, dest
+            )
+        };
+        assert!(parse_monkeys(&monkey(0, 0)).is_ok());
+        assert!(parse_monkeys(&monkey(1, 0)).is_err());
+        assert!(parse_monkeys(&monkey(0, 1)).is_err());
+    }
+}
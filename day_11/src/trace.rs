@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use crate::models::Monkey;
+use crate::worry::WorryRules;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Throw {
+    pub round: usize,
+    pub from: usize,
+    pub to: usize,
+    /// Worry level of the item when thrown.
+    pub worry: usize,
+}
+
+/// Path of a single item through the monkeys. Items never interact, so each one can be followed on
+/// its own: once it is held by the same monkey with the same worry level at the start of two rounds,
+/// it repeats the same throws forever.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ItemTrace {
+    /// Monkey holding the item at the start, and its initial worry level.
+    pub start: (usize, usize),
+    /// Every throw during the simulated rounds, in order.
+    pub history: Vec<Throw>,
+    /// Index in `history` of the first throw of each simulated round, and the end of the history.
+    round_starts: Vec<usize>,
+    /// First round of the cycle and its length, if found within the simulated rounds.
+    pub cycle: Option<(usize, usize)>,
+}
+
+impl ItemTrace {
+    /// Follows the item until its state repeats, or for `max_rounds` rounds.
+    pub fn trace(monkeys: &[Monkey], rules: &WorryRules, start: (usize, usize), max_rounds: u64) -> Result<ItemTrace, String> {
+        let mut trace = ItemTrace { start, history: vec![], round_starts: vec![], cycle: None };
+        let mut seen: HashMap<(usize, usize), usize> = HashMap::new();
+        let (mut monkey, mut worry) = start;
+        for round in 0.. {
+            if round as u64 >= max_rounds {
+                break;
+            }
+            if let Some(first) = seen.insert((monkey, worry), round) {
+                trace.cycle = Some((first, round - first));
+                break;
+            }
+            trace.round_starts.push(trace.history.len());
+            // Throws to monkeys further in the order are handled during the same round, and throws
+            // back to the same monkey, like the earlier ones, during the next round.
+            loop {
+                let from = &monkeys[monkey];
+                worry = rules.inspect(&from.operation, worry)?;
+                let to = if worry.is_multiple_of(from.test_divisible_by) {
+                    from.test_dest_true
+                } else {
+                    from.test_dest_false
+                };
+                trace.history.push(Throw { round, from: monkey, to, worry });
+                let next_round = to <= monkey;
+                monkey = to;
+                if next_round {
+                    break;
+                }
+            }
+        }
+        trace.round_starts.push(trace.history.len());
+        Ok(trace)
+    }
+
+    /// Number of rounds in `history`.
+    pub fn simulated_rounds(&self) -> usize {
+        self.round_starts.len() - 1
+    }
+
+    /// Throws during the given round, or `None` if it is past the simulated rounds without a cycle.
+    pub fn round_throws(&self, round: u64) -> Option<&[Throw]> {
+        let round = match self.cycle {
+            Some((start, length)) if round >= start as u64 => start + ((round - start as u64) % length as u64) as usize,
+            _ if round < self.simulated_rounds() as u64 => round as usize,
+            _ => return None,
+        };
+        Some(&self.history[self.round_starts[round]..self.round_starts[round + 1]])
+    }
+
+    /// Number of inspections of the item by each monkey during the given number of rounds.
+    pub fn inspection_counts(&self, monkey_count: usize, rounds: u64) -> Result<Vec<u64>, String> {
+        let mut counts = vec![0; monkey_count];
+        let mut add_rounds = |rounds: std::ops::Range<usize>, times: u64| {
+            for throw in &self.history[self.round_starts[rounds.start]..self.round_starts[rounds.end]] {
+                counts[throw.from] += times;
+            }
+        };
+        match self.cycle {
+            Some((start, length)) if rounds > start as u64 => {
+                let (cycles, remainder) = ((rounds - start as u64) / length as u64, (rounds - start as u64) % length as u64);
+                add_rounds(0..start, 1);
+                add_rounds(start..start + length, cycles);
+                add_rounds(start..start + remainder as usize, 1);
+            }
+            _ if rounds <= self.simulated_rounds() as u64 => add_rounds(0..rounds as usize, 1),
+            _ => return Err(format!("Item {:?} was only traced for {} rounds", self.start, self.simulated_rounds())),
+        }
+        Ok(counts)
+    }
+}
+
+/// Traces of all the items, in the order of the monkeys holding them at the start.
+pub fn trace_items(monkeys: &[Monkey], rules: &WorryRules, max_rounds: u64) -> Result<Vec<ItemTrace>, String> {
+    monkeys
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, monkey)| monkey.items.iter().map(move |item| (idx, *item)))
+        .map(|start| ItemTrace::trace(monkeys, rules, start, max_rounds))
+        .collect()
+}
+
+/// Inspection counts of each monkey after any number of rounds, extrapolated from the cycles of
+/// the items.
+pub fn extrapolated_inspection_counts(monkeys: &[Monkey], rules: &WorryRules, rounds: u64) -> Result<Vec<u64>, String> {
+    let mut counts = vec![0; monkeys.len()];
+    for trace in trace_items(monkeys, rules, rounds)? {
+        for (total, count) in counts.iter_mut().zip(trace.inspection_counts(monkeys.len(), rounds)?) {
+            *total += count;
+        }
+    }
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inspection_counts;
+    use crate::parsers::parse_monkeys;
+
+    static TEST_INPUT: &str = "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1";
+
+    #[test]
+    fn test_matches_round_simulation() {
+        let monkeys = parse_monkeys(TEST_INPUT).unwrap();
+        // Without relief, worry levels eventually overflow.
+        for (relief, max_rounds) in [(1, 1000), (3, 20)] {
+            let rules = WorryRules::new(&monkeys, relief).unwrap();
+            for rounds in [0, 1, max_rounds / 2, max_rounds] {
+                let expected: Vec<u64> = inspection_counts(&monkeys, rounds, &rules).unwrap().iter().map(|c| *c as u64).collect();
+                assert_eq!(extrapolated_inspection_counts(&monkeys, &rules, rounds as u64).unwrap(), expected);
+            }
+        }
+
+        // An item thrown by a monkey to itself is inspected again in the next round.
+        let monkeys = parse_monkeys("Monkey 0:
+  Starting items: 1
+  Operation: new = old + 1
+  Test: divisible by 2
+    If true: throw to monkey 0
+    If false: throw to monkey 0").unwrap();
+        let rules = WorryRules::new(&monkeys, 1).unwrap();
+        assert_eq!(inspection_counts(&monkeys, 5, &rules), Ok(vec![5]));
+        assert_eq!(extrapolated_inspection_counts(&monkeys, &rules, 5), Ok(vec![5]));
+    }
+
+    #[test]
+    fn test_history() {
+        let monkeys = parse_monkeys(TEST_INPUT).unwrap();
+        let rules = WorryRules::new(&monkeys, 3).unwrap();
+        let trace = ItemTrace::trace(&monkeys, &rules, (0, 79), 20).unwrap();
+        // 79 * 19 / 3 = 500, thrown to monkey 3, then 503 / 3 = 167, thrown to monkey 1 for the next round.
+        assert_eq!(
+            trace.round_throws(0),
+            Some(&[Throw { round: 0, from: 0, to: 3, worry: 500 }, Throw { round: 0, from: 3, to: 1, worry: 167 }][..])
+        );
+        assert_eq!(trace.round_throws(0), Some(&trace.history[..2]));
+    }
+
+    #[test]
+    fn test_cycles() {
+        let monkeys = parse_monkeys(TEST_INPUT).unwrap();
+        let rules = WorryRules::new(&monkeys, 1).unwrap();
+        let traces = trace_items(&monkeys, &rules, u64::MAX).unwrap();
+        assert_eq!(traces.len(), 10);
+        for trace in &traces {
+            let (start, length) = trace.cycle.unwrap();
+            assert_eq!(trace.simulated_rounds(), start + length);
+            assert_eq!(trace.round_throws((start + length) as u64), trace.round_throws(start as u64));
+        }
+        let mut counts = extrapolated_inspection_counts(&monkeys, &rules, 10000).unwrap();
+        counts.sort();
+        assert_eq!(counts[2] * counts[3], 2713310158);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::models::{Expr, Monkey};
+use num_bigint::BigUint;
+
+pub fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Least common multiple, or `None` if it overflows.
+pub fn checked_lcm(a: usize, b: usize) -> Option<usize> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// How worry levels evolve when a monkey inspects an item.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct WorryRules {
+    /// Worry levels are divided by this after each inspection.
+    pub relief: usize,
+    /// Worry levels are kept modulo this number, if possible.
+    pub modulus: Option<usize>,
+}
+
+impl WorryRules {
+    /// Without relief, worry levels can be kept modulo the LCM of all the test divisors, which
+    /// changes none of the tests. With relief, the divisions do not commute with the modulo, so
+    /// worry levels are exact and an overflow is an error.
+    pub fn new(monkeys: &[Monkey], relief: usize) -> Result<WorryRules, String> {
+        if relief == 0 {
+            return Err("Relief cannot divide by 0".to_owned());
+        }
+        if let Some(idx) = monkeys.iter().position(|m| m.test_divisible_by == 0) {
+            return Err(format!("Monkey {} tests divisibility by 0", idx));
+        }
+        let modulus = if relief == 1 {
+            let lcm = monkeys.iter().try_fold(1, |lcm, m| checked_lcm(lcm, m.test_divisible_by));
+            Some(lcm.ok_or("The LCM of the test divisors overflows")?)
+        } else {
+            None
+        };
+        Ok(WorryRules { relief, modulus })
+    }
+
+    pub fn inspect(&self, operation: &Expr, old: usize) -> Result<usize, String> {
+        match self.modulus {
+            Some(modulus) => Ok(operation.eval_mod(old, modulus)),
+            None => operation
+                .checked_eval(old)
+                .map(|new| new / self.relief)
+                .ok_or_else(|| format!("Worry level overflow: new = {} with old = {}", operation, old)),
+        }
+    }
+}
+
+/// Inspection counts after running the given number of rounds with exact, unbounded worry levels.
+/// This is only practical for small numbers of rounds, to validate the bounded simulation.
+pub fn exact_inspection_counts(monkeys: &[Monkey], rounds: usize, relief: usize) -> Vec<usize> {
+    let relief = BigUint::from(relief);
+    let mut items: Vec<Vec<BigUint>> =
+        monkeys.iter().map(|m| m.items.iter().map(|item| BigUint::from(*item)).collect()).collect();
+    let mut counts = vec![0; monkeys.len()];
+    for _ in 0..rounds {
+        for (idx, monkey) in monkeys.iter().enumerate() {
+            let inspected = std::mem::take(&mut items[idx]);
+            counts[idx] += inspected.len();
+            let divisor = BigUint::from(monkey.test_divisible_by);
+            for item in inspected {
+                let new = monkey.operation.eval_big(&item) / &relief;
+                let throw_to = if (&new % &divisor).bits() == 0 {
+                    monkey.test_dest_true
+                } else {
+                    monkey.test_dest_false
+                };
+                items[throw_to].push(new);
+            }
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::parse_monkeys;
+    use crate::inspection_counts;
+
+    #[test]
+    fn test_lcm() {
+        assert_eq!(checked_lcm(4, 6), Some(12));
+        assert_eq!(checked_lcm(7, 1), Some(7));
+        assert_eq!(checked_lcm(usize::MAX, usize::MAX - 1), None);
+    }
+
+    #[test]
+    fn test_rules() {
+        let monkeys = parse_monkeys(
+            "Monkey 0:
+  Starting items: 1
+  Operation: new = old
+  Test: divisible by 4
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 2
+  Operation: new = old * old
+  Test: divisible by 6
+    If true: throw to monkey 0
+    If false: throw to monkey 1",
+        )
+        .unwrap();
+        assert_eq!(WorryRules::new(&monkeys, 1), Ok(WorryRules { relief: 1, modulus: Some(12) }));
+        assert_eq!(WorryRules::new(&monkeys, 3), Ok(WorryRules { relief: 3, modulus: None }));
+        assert!(WorryRules::new(&monkeys, 0).is_err());
+
+        let squares = WorryRules { relief: 1, modulus: None };
+        assert!(squares.inspect(&monkeys[1].operation, 1 << 40).is_err());
+    }
+
+    #[test]
+    fn test_matches_exact_simulation() {
+        let monkeys = parse_monkeys(
+            "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 8
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 6
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 9
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 4
+    If true: throw to monkey 0
+    If false: throw to monkey 1",
+        )
+        .unwrap();
+        for relief in [1, 3] {
+            let rules = WorryRules::new(&monkeys, relief).unwrap();
+            assert_eq!(inspection_counts(&monkeys, 15, &rules).unwrap(), exact_inspection_counts(&monkeys, 15, relief));
+        }
+    }
+}
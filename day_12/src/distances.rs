@@ -0,0 +1,148 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use crate::rules::ClimbingRules;
+use crate::{Coords, HeightMap};
+
+/// Cost of the shortest path between every cell and an origin, computed with a single search: a
+/// breadth-first search when all the steps cost the same, Dijkstra's algorithm otherwise.
+#[derive(PartialEq, Debug, Clone)]
+pub struct DistanceField {
+    distances: Vec<Vec<Option<usize>>>,
+}
+
+impl DistanceField {
+    /// Distances to the end, searching backwards from it.
+    pub fn to_end(map: &HeightMap, rules: &ClimbingRules) -> DistanceField {
+        DistanceField::search(map, map.end, rules, |coords| map.reverse_moves(coords, rules))
+    }
+
+    /// Distances from the start.
+    pub fn from_start(map: &HeightMap, rules: &ClimbingRules) -> DistanceField {
+        DistanceField::search(map, map.start, rules, |coords| map.moves(coords, rules))
+    }
+
+    fn search(
+        map: &HeightMap,
+        origin: Coords,
+        rules: &ClimbingRules,
+        moves: impl Fn(Coords) -> Vec<(Coords, usize)>,
+    ) -> DistanceField {
+        let mut distances = vec![vec![None; map.x_max + 1]; map.y_max + 1];
+        distances[origin.y][origin.x] = Some(0);
+
+        if rules.is_uniform() {
+            let mut queue = VecDeque::new();
+            queue.push_back((0, origin));
+            while let Some((depth, current_point)) = queue.pop_front() {
+                for (neighbor, _) in moves(current_point) {
+                    if distances[neighbor.y][neighbor.x].is_none() {
+                        distances[neighbor.y][neighbor.x] = Some(depth + 1);
+                        queue.push_back((depth + 1, neighbor));
+                    }
+                }
+            }
+        } else {
+            let mut queue = BinaryHeap::new();
+            queue.push(Reverse((0, origin)));
+            while let Some(Reverse((cost, current_point))) = queue.pop() {
+                if distances[current_point.y][current_point.x].is_some_and(|best| best < cost) {
+                    continue;
+                }
+                for (neighbor, step_cost) in moves(current_point) {
+                    let new_cost = cost + step_cost;
+                    if distances[neighbor.y][neighbor.x].is_none_or(|best| new_cost < best) {
+                        distances[neighbor.y][neighbor.x] = Some(new_cost);
+                        queue.push(Reverse((new_cost, neighbor)));
+                    }
+                }
+            }
+        }
+        DistanceField { distances }
+    }
+
+    /// Cost of the shortest path between `coords` and the origin, or `None` if there is no path.
+    pub fn get(&self, coords: Coords) -> Option<usize> {
+        self.distances[coords.y][coords.x]
+    }
+
+    /// The cell closest to the origin among the given ones, with its distance.
+    pub fn closest(&self, cells: impl Iterator<Item = Coords>) -> Option<(Coords, usize)> {
+        cells.filter_map(|coords| self.get(coords).map(|d| (coords, d))).min_by_key(|(_, d)| *d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+    use crate::rules::StepCost;
+
+    #[test]
+    fn test_distance_field() {
+        let map = parse_input(
+            "Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi",
+        )
+        .unwrap();
+        let field = DistanceField::to_end(&map, &ClimbingRules::default());
+        assert_eq!(field.get(map.start), Some(31));
+        assert_eq!(field.get(map.end), Some(0));
+        assert_eq!(field.get(Coords { x: 0, y: 4 }), Some(29));
+        assert_eq!(field.closest([Coords { x: 0, y: 0 }, Coords { x: 4, y: 1 }].into_iter()), Some((Coords { x: 4, y: 1 }, 2)));
+    }
+
+    #[test]
+    fn test_unreachable_cells() {
+        let map = parse_input("SbcdE\nazzzz").unwrap();
+        let field = DistanceField::to_end(&map, &ClimbingRules::default());
+        assert_eq!(field.get(map.start), None);
+        assert_eq!(field.get(Coords { x: 1, y: 1 }), Some(4));
+        assert_eq!(field.closest([map.start].into_iter()), None);
+    }
+
+    #[test]
+    fn test_rules() {
+        let map = parse_input("SbaE\naaaa").unwrap();
+        let default = DistanceField::from_start(&map, &ClimbingRules::default());
+        assert_eq!(default.get(Coords { x: 2, y: 0 }), Some(2));
+        assert_eq!(default.get(map.end), None);
+
+        // Changing height costs 5 more, so going around the `b` is cheaper.
+        let weighted = ClimbingRules {
+            cost: StepCost::ByHeightDifference(|difference| 1 + 5 * difference.unsigned_abs() as usize),
+            ..ClimbingRules::default()
+        };
+        assert_eq!(DistanceField::from_start(&map, &weighted).get(Coords { x: 2, y: 0 }), Some(4));
+
+        let flat = ClimbingRules { max_climb: 0, ..ClimbingRules::default() };
+        assert_eq!(DistanceField::from_start(&map, &flat).get(Coords { x: 1, y: 0 }), None);
+        assert_eq!(DistanceField::from_start(&map, &flat).get(Coords { x: 2, y: 0 }), Some(4));
+
+        let diagonal = ClimbingRules { diagonal_moves: true, ..ClimbingRules::default() };
+        assert_eq!(DistanceField::from_start(&map, &diagonal).get(Coords { x: 2, y: 1 }), Some(2));
+    }
+
+    #[test]
+    fn test_weighted_search_both_ways() {
+        let map = parse_input(
+            "Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi",
+        )
+        .unwrap();
+        let rules = ClimbingRules {
+            cost: StepCost::ByHeightDifference(|difference| if difference > 0 { 3 } else { 1 }),
+            ..ClimbingRules::default()
+        };
+        let to_end = DistanceField::to_end(&map, &rules);
+        let from_start = DistanceField::from_start(&map, &rules);
+        assert_eq!(to_end.get(map.start), from_start.get(map.end));
+        // 25 climbs are needed to reach `z`.
+        assert!(to_end.get(map.start).unwrap() >= 31 + 2 * 25);
+    }
+}
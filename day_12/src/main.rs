@@ -1,17 +1,66 @@
 use ascii::{AsAsciiStr, AsAsciiStrError, AsciiChar};
 use std::collections::{HashSet, VecDeque};
 use std::error::Error;
+use crate::distances::DistanceField;
+use crate::paths::{render_cells, render_path, shortest_path, shortest_path_cells};
+use crate::rules::{ClimbingRules, StepCost};
+
+mod distances;
+mod paths;
+mod rules;
 
 static INPUT: &str = include_str!("input");
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
     let map = parse_input(INPUT)?;
-    println!("Part 1 result: {}", part_1(&map)?);
-    println!("Part 2 result: {}", part_2(&map)?);
+
+    match args.as_slice() {
+        ["route", rules @ ..] => {
+            let rules = parse_rules(rules)?;
+            println!("{}", render_path(&map, &shortest_path(&map, &rules).ok_or("No route")?));
+        }
+        ["cells", rules @ ..] => {
+            let cells = shortest_path_cells(&map, &parse_rules(rules)?);
+            println!("{}", render_cells(&map, &cells));
+            println!("{} cells on a shortest path", cells.len());
+        }
+        [] => {
+            println!("Part 1 result: {}", part_1(&map)?);
+            println!("Part 2 result: {}", part_2(&map)?);
+        }
+        _ => Err("Usage: day_12 [route | cells] [max-climb=<n>] [max-descent=<n>] [diagonal] [cost=uniform|climb|steep]")?,
+    }
 
     Ok(())
 }
 
+/// Reads climbing rules such as `max-climb=2 diagonal cost=steep`, starting from the puzzle's ones.
+fn parse_rules(options: &[&str]) -> Result<ClimbingRules, String> {
+    let mut rules = ClimbingRules::default();
+    for option in options {
+        let invalid = || format!("Invalid option {}", option);
+        match option.split_once('=') {
+            None if *option == "diagonal" => rules.diagonal_moves = true,
+            Some(("max-climb", height)) => rules.max_climb = height.parse().map_err(|_| invalid())?,
+            Some(("max-descent", height)) => rules.max_descent = Some(height.parse().map_err(|_| invalid())?),
+            Some(("cost", cost)) => {
+                rules.cost = match cost {
+                    "uniform" => StepCost::Uniform,
+                    // Each height climbed costs one more step.
+                    "climb" => StepCost::ByHeightDifference(|difference| 1 + difference.max(0) as usize),
+                    // Each height climbed or descended costs one more step.
+                    "steep" => StepCost::ByHeightDifference(|difference| 1 + difference.unsigned_abs() as usize),
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(rules)
+}
+
 fn part_1(map: &HeightMap) -> Result<usize, String> {
     let mut queue = VecDeque::new();
     let mut explored = HashSet::new();
@@ -33,16 +82,14 @@ fn part_1(map: &HeightMap) -> Result<usize, String> {
 }
 
 fn part_2(map: &HeightMap) -> Result<usize, String> {
-    // Smarter solution would be to search from the end...
-    (0..=map.x_max)
-        .flat_map(|x| (0..=map.y_max).map(move |y| Coords { x, y }))
-        .filter(|coord| map.get(*coord) == AsciiChar::a)
-        .flat_map(|start| part_1(&HeightMap { start, ..map.clone() }).ok())
-        .min()
+    let lowest_cells = map.cells().filter(|coords| map.get(*coords) == AsciiChar::a);
+    DistanceField::to_end(map, &ClimbingRules::default())
+        .closest(lowest_cells)
+        .map(|(_, distance)| distance)
         .ok_or("No result".to_owned())
 }
 
-#[derive(PartialEq, Copy, Clone, Debug, Eq, Hash)]
+#[derive(PartialEq, Copy, Clone, Debug, Eq, Hash, PartialOrd, Ord)]
 struct Coords {
     x: usize,
     y: usize,
@@ -61,26 +108,47 @@ impl HeightMap {
     fn get(&self, coords: Coords) -> AsciiChar {
         self.squares[coords.y][coords.x]
     }
-    fn accessible_neighbors(&self, Coords { x, y }: Coords) -> Vec<Coords> {
-        let current_height = self.get(Coords { x, y }).as_byte();
+    fn cells(&self) -> impl Iterator<Item = Coords> + '_ {
+        (0..=self.y_max).flat_map(move |y| (0..=self.x_max).map(move |x| Coords { x, y }))
+    }
+    fn accessible_neighbors(&self, coords: Coords) -> Vec<Coords> {
+        self.moves(coords, &ClimbingRules::default()).into_iter().map(|(dest, _)| dest).collect()
+    }
+    /// Cells accessible from `coords` under the given rules, with the cost of the step.
+    fn moves(&self, coords: Coords, rules: &ClimbingRules) -> Vec<(Coords, usize)> {
+        let height = self.get(coords).as_byte();
+        self.neighbors(coords, rules.diagonal_moves)
+            .into_iter()
+            .filter_map(|dest| rules.step_cost(height, self.get(dest).as_byte()).map(|cost| (dest, cost)))
+            .collect()
+    }
+    /// Cells from which `coords` is accessible under the given rules, with the cost of the step.
+    fn reverse_moves(&self, coords: Coords, rules: &ClimbingRules) -> Vec<(Coords, usize)> {
+        let height = self.get(coords).as_byte();
+        self.neighbors(coords, rules.diagonal_moves)
+            .into_iter()
+            .filter_map(|src| rules.step_cost(self.get(src).as_byte(), height).map(|cost| (src, cost)))
+            .collect()
+    }
+    fn neighbors(&self, Coords { x, y }: Coords, diagonal: bool) -> Vec<Coords> {
         let mut directions = vec![];
-        let mut maybe_add = |dest: Coords| {
-            let dest_height = self.get(dest).as_byte();
-            if dest_height <= current_height || dest_height == current_height + 1 {
-                directions.push(dest);
+        let mut maybe_add = |dx: isize, dy: isize| {
+            let (dest_x, dest_y) = (x.checked_add_signed(dx), y.checked_add_signed(dy));
+            if let (Some(x), Some(y)) = (dest_x, dest_y) {
+                if x <= self.x_max && y <= self.y_max {
+                    directions.push(Coords { x, y });
+                }
             }
         };
-        if x > 0 {
-            maybe_add(Coords { x: x - 1, y });
-        }
-        if x < self.x_max {
-            maybe_add(Coords { x: x + 1, y });
-        }
-        if y > 0 {
-            maybe_add(Coords { x, y: y - 1 });
-        }
-        if y < self.y_max {
-            maybe_add(Coords { x, y: y + 1 });
+        maybe_add(-1, 0);
+        maybe_add(1, 0);
+        maybe_add(0, -1);
+        maybe_add(0, 1);
+        if diagonal {
+            maybe_add(-1, -1);
+            maybe_add(1, -1);
+            maybe_add(-1, 1);
+            maybe_add(1, 1);
         }
         directions
     }
@@ -209,6 +277,31 @@ abdefghi",
         assert_eq!(part_2(&test_input()).unwrap(), 29);
     }
 
+    #[test]
+    fn test_neighbors() {
+        let map = test_input();
+        assert_eq!(
+            vec_to_set(map.accessible_neighbors(Coords { x: 2, y: 2 })),
+            vec_to_set(vec![Coords { x: 2, y: 1 }, Coords { x: 1, y: 2 }, Coords { x: 2, y: 3 }])
+        );
+        assert_eq!(
+            vec_to_set(map.reverse_moves(Coords { x: 3, y: 2 }, &ClimbingRules::default()).into_iter().map(|(src, _)| src).collect()),
+            vec_to_set(vec![Coords { x: 3, y: 1 }, Coords { x: 4, y: 2 }, Coords { x: 3, y: 3 }])
+        );
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = parse_rules(&["max-climb=2", "max-descent=3", "diagonal", "cost=steep"]).unwrap();
+        assert_eq!((rules.max_climb, rules.max_descent, rules.diagonal_moves), (2, Some(3), true));
+        assert_eq!(rules.step_cost(b'c', b'a'), Some(3));
+        let rules = parse_rules(&["cost=climb"]).unwrap();
+        assert_eq!((rules.step_cost(b'a', b'b'), rules.step_cost(b'c', b'a')), (Some(2), Some(1)));
+        assert!(parse_rules(&[]).unwrap().is_uniform());
+        assert!(parse_rules(&["cost=free"]).is_err());
+        assert!(parse_rules(&["max-climb=-1"]).is_err());
+    }
+
     fn vec_to_set(vec: Vec<Coords>) -> HashSet<Coords> {
         HashSet::from_iter(vec)
     }
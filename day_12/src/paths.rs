@@ -0,0 +1,192 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use crate::distances::DistanceField;
+use crate::rules::ClimbingRules;
+use crate::{Coords, HeightMap};
+
+/// One of the shortest paths from the start to the end, both included.
+pub fn shortest_path(map: &HeightMap, rules: &ClimbingRules) -> Option<Vec<Coords>> {
+    let to_end = DistanceField::to_end(map, rules);
+    to_end.get(map.start)?;
+    // Depth-first walk along the steps which bring the end closer by exactly their cost. Steps
+    // costing 0 keep the distance the same, so cells are not visited twice, and the walk backtracks
+    // out of the cells from which such steps only lead back.
+    let mut path = vec![map.start];
+    let mut visited = HashSet::from([map.start]);
+    while let Some(&current) = path.last() {
+        if current == map.end {
+            return Some(path);
+        }
+        let distance = to_end.get(current)?;
+        let next = map
+            .moves(current, rules)
+            .into_iter()
+            .find(|(neighbor, cost)| !visited.contains(neighbor) && to_end.get(*neighbor) == distance.checked_sub(*cost));
+        match next {
+            Some((next, _)) => {
+                visited.insert(next);
+                path.push(next);
+            }
+            None => {
+                path.pop();
+            }
+        }
+    }
+    None
+}
+
+/// All the cells which are on at least one of the shortest paths from the start to the end.
+pub fn shortest_path_cells(map: &HeightMap, rules: &ClimbingRules) -> HashSet<Coords> {
+    let from_start = DistanceField::from_start(map, rules);
+    let to_end = DistanceField::to_end(map, rules);
+    let Some(length) = to_end.get(map.start) else {
+        return HashSet::new();
+    };
+    map.cells()
+        .filter(|coords| match (from_start.get(*coords), to_end.get(*coords)) {
+            (Some(d1), Some(d2)) => d1 + d2 == length,
+            _ => false,
+        })
+        .collect()
+}
+
+/// Draws the path like the puzzle does, with an arrow towards the next cell on each cell of the
+/// path (diagonal ones for diagonal moves), `E` at the end and `.` elsewhere.
+pub fn render_path(map: &HeightMap, path: &[Coords]) -> String {
+    let mut grid = vec![vec!['.'; map.x_max + 1]; map.y_max + 1];
+    for step in path.windows(2) {
+        let (from, to) = (step[0], step[1]);
+        grid[from.y][from.x] = match (to.x.cmp(&from.x), to.y.cmp(&from.y)) {
+            (Ordering::Greater, Ordering::Equal) => '>',
+            (Ordering::Less, Ordering::Equal) => '<',
+            (Ordering::Equal, Ordering::Greater) => 'v',
+            (Ordering::Equal, Ordering::Less) => '^',
+            (Ordering::Greater, Ordering::Greater) => '↘',
+            (Ordering::Greater, Ordering::Less) => '↗',
+            (Ordering::Less, Ordering::Greater) => '↙',
+            _ => '↖',
+        };
+    }
+    if let Some(last) = path.last() {
+        grid[last.y][last.x] = 'E';
+    }
+    render(grid)
+}
+
+/// Draws the height map, with the given cells in uppercase.
+pub fn render_cells(map: &HeightMap, cells: &HashSet<Coords>) -> String {
+    let grid = (0..=map.y_max)
+        .map(|y| {
+            (0..=map.x_max)
+                .map(|x| {
+                    let height = map.get(Coords { x, y }).as_char();
+                    if cells.contains(&Coords { x, y }) {
+                        height.to_ascii_uppercase()
+                    } else {
+                        height
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    render(grid)
+}
+
+fn render(grid: Vec<Vec<char>>) -> String {
+    grid.into_iter().map(|row| row.into_iter().collect::<String>()).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+    use crate::rules::StepCost;
+
+    fn test_input() -> HeightMap {
+        parse_input(
+            "Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let map = test_input();
+        let path = shortest_path(&map, &ClimbingRules::default()).unwrap();
+        assert_eq!(path.len(), 32);
+        assert_eq!((path[0], path[31]), (map.start, map.end));
+        assert!(path.windows(2).all(|step| map.accessible_neighbors(step[0]).contains(&step[1])));
+
+        let expected = ">>vv<<<<
+..vvv<<^
+..vv>E^^
+..v>>>^^
+..>>>>>^";
+        assert_eq!(render_path(&map, &path), expected);
+        assert!(shortest_path(&parse_input("SbcdE\nazzzz").unwrap(), &ClimbingRules::default()).is_none());
+    }
+
+    #[test]
+    fn test_shortest_path_cells() {
+        let map = test_input();
+        let cells = shortest_path_cells(&map, &ClimbingRules::default());
+        let path = shortest_path(&map, &ClimbingRules::default()).unwrap();
+        assert!(path.iter().all(|coords| cells.contains(coords)));
+        let expected = "AABQPONM
+ABCRYXXL
+aCCSZZXK
+aCCTUVWJ
+abDEFGHI";
+        assert_eq!(render_cells(&map, &cells), expected);
+        assert_eq!(cells.len(), 36);
+    }
+
+    #[test]
+    fn test_weighted_path() {
+        let map = test_input();
+        let rules = ClimbingRules {
+            diagonal_moves: true,
+            cost: StepCost::ByHeightDifference(|difference| if difference > 0 { 3 } else { 1 }),
+            ..ClimbingRules::default()
+        };
+        let path = shortest_path(&map, &rules).unwrap();
+        let cost: usize = path
+            .windows(2)
+            .map(|step| rules.step_cost(map.get(step[0]).as_byte(), map.get(step[1]).as_byte()).unwrap())
+            .sum();
+        assert_eq!(Some(cost), DistanceField::to_end(&map, &rules).get(map.start));
+        assert!(render_path(&map, &path).contains(|c| "↖↗↘↙".contains(c)));
+    }
+
+    #[test]
+    fn test_zero_cost_steps() {
+        let map = parse_input("SaaE").unwrap();
+        let rules = ClimbingRules {
+            max_climb: 25,
+            cost: StepCost::ByHeightDifference(|difference| if difference == 0 { 0 } else { 1 }),
+            ..ClimbingRules::default()
+        };
+        assert_eq!(shortest_path(&map, &rules).map(|path| path.len()), Some(4));
+
+        // Free steps lead back and forth across the `b` plateau.
+        let map = parse_input("abbbb\nSbbbE\nbbbbb").unwrap();
+        let path = shortest_path(&map, &rules).unwrap();
+        let cost: usize = path
+            .windows(2)
+            .map(|step| rules.step_cost(map.get(step[0]).as_byte(), map.get(step[1]).as_byte()).unwrap())
+            .sum();
+        assert_eq!((path[0], path[path.len() - 1]), (map.start, map.end));
+        assert_eq!(cost, 2);
+        assert_eq!(path.iter().collect::<HashSet<_>>().len(), path.len());
+
+        let rules = ClimbingRules {
+            cost: StepCost::ByHeightDifference(|difference| if difference == 0 { 1 } else { 2 }),
+            ..rules
+        };
+        assert_eq!(shortest_path(&parse_input("SaaE").unwrap(), &rules).map(|path| path.len()), Some(4));
+    }
+}
@@ -0,0 +1,76 @@
+#[derive(Debug, Clone, Copy)]
+pub enum StepCost {
+    /// Every step costs 1, so a breadth-first search finds the shortest paths.
+    Uniform,
+    /// Cost of a step given the height difference, positive when climbing.
+    ByHeightDifference(fn(i32) -> usize),
+}
+
+/// Which steps are allowed on the height map, and what they cost.
+#[derive(Debug, Clone, Copy)]
+pub struct ClimbingRules {
+    pub max_climb: u8,
+    /// `None` to allow descending any height.
+    pub max_descent: Option<u8>,
+    pub diagonal_moves: bool,
+    pub cost: StepCost,
+}
+
+impl Default for ClimbingRules {
+    /// The puzzle's rules: climb at most one, descend any height, no diagonal moves.
+    fn default() -> Self {
+        ClimbingRules { max_climb: 1, max_descent: None, diagonal_moves: false, cost: StepCost::Uniform }
+    }
+}
+
+impl ClimbingRules {
+    pub fn is_uniform(&self) -> bool {
+        matches!(self.cost, StepCost::Uniform)
+    }
+
+    /// Cost of a step between the given heights, or `None` if it is not allowed.
+    pub fn step_cost(&self, from_height: u8, to_height: u8) -> Option<usize> {
+        let difference = to_height as i32 - from_height as i32;
+        if difference > self.max_climb as i32 {
+            return None;
+        }
+        if self.max_descent.is_some_and(|max_descent| -difference > max_descent as i32) {
+            return None;
+        }
+        match self.cost {
+            StepCost::Uniform => Some(1),
+            StepCost::ByHeightDifference(cost) => Some(cost(difference)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_cost() {
+        let rules = ClimbingRules::default();
+        assert_eq!(rules.step_cost(b'a', b'b'), Some(1));
+        assert_eq!(rules.step_cost(b'a', b'c'), None);
+        assert_eq!(rules.step_cost(b'z', b'a'), Some(1));
+
+        let rules = ClimbingRules {
+            max_climb: 2,
+            max_descent: Some(1),
+            diagonal_moves: true,
+            cost: StepCost::ByHeightDifference(|difference| 1 + difference.unsigned_abs() as usize),
+        };
+        assert_eq!(rules.step_cost(b'a', b'c'), Some(3));
+        assert_eq!(rules.step_cost(b'c', b'b'), Some(2));
+        assert_eq!(rules.step_cost(b'c', b'a'), None);
+        assert!(!rules.is_uniform());
+
+        let free_flat_steps = ClimbingRules {
+            cost: StepCost::ByHeightDifference(|difference| if difference == 0 { 0 } else { 1 }),
+            ..ClimbingRules::default()
+        };
+        assert_eq!(free_flat_steps.step_cost(b'a', b'a'), Some(0));
+        assert_eq!(free_flat_steps.step_cost(b'a', b'b'), Some(1));
+    }
+}
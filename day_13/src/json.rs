@@ -0,0 +1,62 @@
+use crate::parser_generator::Data;
+use serde_json::Value;
+
+impl From<&Data> for Value {
+    fn from(data: &Data) -> Value {
+        match data {
+            Data::Int(n) => Value::from(*n),
+            Data::List(list) => Value::Array(list.iter().map(Value::from).collect()),
+        }
+    }
+}
+
+impl TryFrom<&Value> for Data {
+    type Error = String;
+
+    fn try_from(value: &Value) -> Result<Data, String> {
+        match value {
+            Value::Number(n) => n
+                .as_i64()
+                .map(Data::Int)
+                .ok_or_else(|| format!("Unsupported packet integer {}", n)),
+            Value::Array(list) => list.iter().map(Data::try_from).collect::<Result<_, _>>().map(Data::List),
+            other => Err(format!("Unsupported packet value {}", other)),
+        }
+    }
+}
+
+/// Reads a packet from its JSON representation, which is the same as the puzzle's syntax.
+pub fn packet_from_json(json: &str) -> Result<Data, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("Cannot read '{}'; {}", json, e))?;
+    Data::try_from(&value)
+}
+
+pub fn packet_to_json(packet: &Data) -> String {
+    Value::from(packet).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser_generator::eval_packet;
+
+    #[test]
+    fn test_round_trip() {
+        for packet in ["[1,[2,[3,[4,[5,6,7]]]],8,9]", "[]", "[[[]]]", "[[4,4],4,4]"] {
+            let data = eval_packet(packet).unwrap();
+            assert_eq!(packet_to_json(&data), packet);
+            assert_eq!(packet_from_json(packet).unwrap().to_string(), packet);
+            assert_eq!(packet_from_json(&data.to_string()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        assert!(packet_from_json("[1,").is_err());
+        assert!(packet_from_json("[1.5]").is_err());
+        assert!(packet_from_json("[9223372036854775808]").is_err());
+        assert_eq!(packet_from_json("[256,-1]").unwrap(), Data::List(vec![Data::Int(256), Data::Int(-1)]));
+        assert_eq!(packet_from_json("[\"a\"]"), Err("Unsupported packet value \"a\"".to_owned()));
+        assert_eq!(packet_from_json(" [ 1 , [ ] ] ").unwrap().to_string(), "[1,[]]");
+    }
+}
@@ -1,20 +1,65 @@
+use crate::json::{packet_from_json, packet_to_json};
 use crate::parser_generator::{eval_packet, Data};
-use common::itertools::{EitherOrBoth, Itertools};
+use crate::ranks::{divider_ranks, PacketIndex};
 use std::cmp::Ordering;
 use std::error::Error;
+use std::io::{self, Read};
 
+mod json;
 mod parser_generator;
+mod ranks;
 
 static INPUT: &str = include_str!("input");
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let packet_pairs = parse_input(INPUT)?;
-    println!("Part 1 result: {}", part_1(&packet_pairs));
-    println!("Part 2 result: {}", part_2(&packet_pairs));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+
+    match args.as_slice() {
+        [] => {
+            let packet_pairs = parse_input(INPUT)?;
+            println!("Part 1 result: {}", part_1(&packet_pairs));
+            println!("Part 2 result: {}", part_2(&packet_pairs));
+        }
+        ["sort", format @ ..] => {
+            let json = match format {
+                [] => false,
+                ["--json"] => true,
+                _ => Err("Usage: day_13 sort [--json]")?,
+            };
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            print!("{}", sort_packets(&input, json)?);
+        }
+        ["rank", probes @ ..] if !probes.is_empty() => {
+            let packets = parse_input(INPUT)?.into_iter().flat_map(|(left, right)| [left, right]).collect();
+            let index = PacketIndex::new(packets);
+            let probes = probes.iter().map(|probe| eval_packet(probe)).collect::<Result<Vec<_>, _>>()?;
+            for (probe, rank) in probes.iter().zip(index.ranks(&probes)) {
+                println!("{}: {} of {}", probe, rank, index.len() + 1);
+            }
+        }
+        _ => Err("Usage: day_13 [sort [--json] | rank <packet>...]")?,
+    }
 
     Ok(())
 }
 
+/// Sorts packets given one per line, in the puzzle's syntax or as JSON, and removes duplicates.
+fn sort_packets(input: &str, json: bool) -> Result<String, String> {
+    let mut packets = input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| if json { packet_from_json(line) } else { eval_packet(line) })
+        .collect::<Result<Vec<Data>, String>>()?;
+    packets.sort();
+    packets.dedup();
+    Ok(packets
+        .iter()
+        .map(|packet| if json { packet_to_json(packet) } else { packet.to_string() } + "\n")
+        .collect())
+}
+
 fn part_1(packet_pairs: &[(Data, Data)]) -> usize {
     packet_pairs
         .iter()
@@ -32,54 +77,15 @@ fn part_2(packet_pairs: &[(Data, Data)]) -> usize {
     let div1 = Data::List(vec![Data::List(vec![Data::Int(2)])]);
     let div2 = Data::List(vec![Data::List(vec![Data::Int(6)])]);
 
-    let mut packets: Vec<Data> = packet_pairs
-        .iter()
-        .flat_map(|(left, right)| [left, right].into_iter())
-        .cloned()
-        .collect();
-    packets.push(div1.clone());
-    packets.push(div2.clone());
-    packets.sort_by(|left, right| match is_in_right_order(left, right) {
-        Some(true) => Ordering::Less,
-        Some(false) => Ordering::Greater,
-        None => Ordering::Equal,
-    });
-
-    let idx1 = packets
-        .iter()
-        .position(|p| p == &div1)
-        .map(|i| i + 1)
-        .unwrap_or(0);
-    let idx2 = packets
-        .iter()
-        .position(|p| p == &div2)
-        .map(|i| i + 1)
-        .unwrap_or(0);
-
-    idx1 * idx2
+    let packets = packet_pairs.iter().flat_map(|(left, right)| [left, right].into_iter());
+    divider_ranks(packets, &[div1, div2]).iter().product()
 }
 
 fn is_in_right_order(left: &Data, right: &Data) -> Option<bool> {
-    match (left, right) {
-        (Data::Int(l), Data::Int(r)) => {
-            if l < r {
-                Some(true)
-            } else if l > r {
-                Some(false)
-            } else {
-                None
-            }
-        }
-        (Data::Int(l), r @ Data::List(_)) => is_in_right_order(&Data::List(vec![Data::Int(*l)]), r),
-        (l @ Data::List(_), Data::Int(r)) => is_in_right_order(l, &Data::List(vec![Data::Int(*r)])),
-        (Data::List(left_list), Data::List(right_list)) => left_list
-            .iter()
-            .zip_longest(right_list.iter())
-            .find_map(|x| match x {
-                EitherOrBoth::Both(l, r) => is_in_right_order(l, r),
-                EitherOrBoth::Left(_) => Some(false),
-                EitherOrBoth::Right(_) => Some(true),
-            }),
+    match left.packet_order(right) {
+        Ordering::Less => Some(true),
+        Ordering::Greater => Some(false),
+        Ordering::Equal => None,
     }
 }
 
@@ -140,11 +146,21 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_sort_packets() -> Result<(), String> {
+        assert_eq!(sort_packets("[[2]]\n[1,[2]]\n\n[2]\n[1, [2]]\n", false)?, "[1,[2]]\n[2]\n[[2]]\n");
+        assert_eq!(sort_packets("[3, -1]\n[]\n[3,-1]", true)?, "[]\n[3,-1]\n");
+        assert!(sort_packets("[\"a\"]", true).is_err());
+        assert!(sort_packets("[1,", false).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_is_in_right_order() -> Result<(), Box<dyn Error>> {
         assert_eq!(is_in_right_order(&Data::Int(1), &Data::Int(2)), Some(true));
         assert_eq!(is_in_right_order(&Data::Int(2), &Data::Int(1)), Some(false));
         assert_eq!(is_in_right_order(&Data::Int(1), &Data::Int(1)), None);
+        assert_eq!(is_in_right_order(&eval_packet("[2]")?, &eval_packet("[[2]]")?), None);
 
         assert_eq!(
             is_in_right_order(&eval_packet("[1,1,3,1,1]")?, &eval_packet("[1,1,5,1,1]")?),
@@ -1,25 +1,81 @@
+use common::itertools::{EitherOrBoth, Itertools};
 use peg::error::ParseError;
+use std::cmp::Ordering;
+use std::fmt;
 use std::fmt::Display;
 
 pub fn eval_packet(expression: &str) -> Result<Data, String> {
-    packet::list(expression).map_err(|e| fmt_err(expression, &e))
+    packet::packet(expression).map_err(|e| fmt_err(expression, &e))
 }
 
 fn fmt_err<T: Display>(expression: &str, error: &ParseError<T>) -> String {
     format!("Cannot evaluate '{}'; {}", expression, error)
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// A packet value. Equality is structural: `[[2]]`, `[2]` and `2` are all different values, even
+/// though they are equivalent in the packet order.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Data {
-    Int(u8),
+    Int(i64),
     List(Vec<Data>),
 }
 
+impl Data {
+    /// The puzzle's ordering, where an integer compares like a list containing only that integer.
+    pub fn packet_order(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Data::Int(l), Data::Int(r)) => l.cmp(r),
+            (Data::Int(l), r @ Data::List(_)) => Data::List(vec![Data::Int(*l)]).packet_order(r),
+            (l @ Data::List(_), Data::Int(r)) => l.packet_order(&Data::List(vec![Data::Int(*r)])),
+            (Data::List(left_list), Data::List(right_list)) => left_list
+                .iter()
+                .zip_longest(right_list.iter())
+                .map(|x| match x {
+                    EitherOrBoth::Both(l, r) => l.packet_order(r),
+                    EitherOrBoth::Left(_) => Ordering::Greater,
+                    EitherOrBoth::Right(_) => Ordering::Less,
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal),
+        }
+    }
+}
+
+/// Sorts by the packet order, and then structurally among equivalent packets (an integer before a
+/// list), so that only equal values compare equal.
+impl Ord for Data {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.packet_order(other).then_with(|| match (self, other) {
+            (Data::Int(l), Data::Int(r)) => l.cmp(r),
+            (Data::Int(_), Data::List(_)) => Ordering::Less,
+            (Data::List(_), Data::Int(_)) => Ordering::Greater,
+            (Data::List(left_list), Data::List(right_list)) => left_list.cmp(right_list),
+        })
+    }
+}
+
+impl PartialOrd for Data {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Data {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Data::Int(n) => write!(f, "{}", n),
+            Data::List(list) => write!(f, "[{}]", list.iter().join(",")),
+        }
+    }
+}
+
 peg::parser! {
     grammar packet() for str {
-        pub rule int() -> Data = n:$(['0'..='9']+) { Data::Int(n.parse().unwrap()) }
-        pub rule list() -> Data = "[" n:(data() ** ",") "]" { Data::List(n) }
+        rule _() = [' ' | '\t']*
+        pub rule int() -> Data = n:$("-"? ['0'..='9']+) {? n.parse().map(Data::Int).or(Err("integer within the i64 range")) }
+        pub rule list() -> Data = "[" _ n:(data() ** (_ "," _)) _ "]" { Data::List(n) }
         pub rule data() -> Data = int() / list()
+        pub rule packet() -> Data = _ p:list() _ { p }
     }
 }
 
@@ -30,7 +86,10 @@ mod tests {
     #[test]
     fn test_parse_int() {
         assert_eq!(packet::int("0").unwrap(), Data::Int(0));
+        assert_eq!(packet::int("-12").unwrap(), Data::Int(-12));
+        assert_eq!(packet::int("9223372036854775807").unwrap(), Data::Int(i64::MAX));
         assert!(packet::int("a").is_err());
+        assert!(packet::int("- 1").is_err());
     }
 
     #[test]
@@ -41,6 +100,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_whitespace_and_overflow() {
+        assert_eq!(eval_packet(" [ 1 ,\t[ ], -3 ] ").unwrap().to_string(), "[1,[],-3]");
+        let error = eval_packet("[1,9223372036854775808]").unwrap_err();
+        assert!(error.contains("integer within the i64 range"), "{}", error);
+    }
+
+    #[test]
+    fn test_display() {
+        let packet = packet::list("[0,[1,[2,3],[]],4]").unwrap();
+        assert_eq!(packet.to_string(), "[0,[1,[2,3],[]],4]");
+    }
+
+    #[test]
+    fn test_ordering() {
+        let packet = |s: &str| packet::list(s).unwrap();
+        assert!(packet("[1,1,3,1,1]") < packet("[1,1,5,1,1]"));
+        assert!(packet("[9]") > packet("[[8,7,6]]"));
+        assert!(packet("[-1]") < packet("[0]"));
+        assert!(packet("[1000]") > packet("[[255,1]]"));
+        assert_eq!(packet("[[2]]").packet_order(&packet("[2]")), Ordering::Equal);
+        assert_eq!(Data::Int(2).packet_order(&packet("[2]")), Ordering::Equal);
+        assert_ne!(packet("[[2]]"), packet("[2]"));
+        assert!(Data::Int(2) < packet("[2]"));
+        assert!(packet("[2]") < packet("[[2]]"));
+
+        let mut packets = vec![packet("[[6]]"), packet("[]"), packet("[2]"), packet("[[2]]"), packet("[[]]"), packet("[2]")];
+        packets.sort();
+        packets.dedup();
+        assert_eq!(packets.iter().map(|p| p.to_string()).join(" "), "[] [[]] [2] [[2]] [[6]]");
+    }
+
     #[test]
     fn test_parse_packet() {
         assert_eq!(
@@ -0,0 +1,116 @@
+use std::cmp::Ordering;
+use crate::parser_generator::Data;
+
+/// 1-based positions the dividers would have if they were added to the packets and everything was
+/// sorted in the packet order, computed by counting in a single pass over the packets, without
+/// copying them. Packets equivalent to a divider come before it, as do equivalent dividers given
+/// earlier.
+pub fn divider_ranks<'a>(packets: impl IntoIterator<Item = &'a Data>, dividers: &[Data]) -> Vec<usize> {
+    let mut ranks: Vec<usize> = dividers
+        .iter()
+        .enumerate()
+        .map(|(idx, divider)| {
+            let smaller_dividers = dividers
+                .iter()
+                .enumerate()
+                .filter(|(other_idx, other)| match other.packet_order(divider) {
+                    Ordering::Less => true,
+                    Ordering::Equal => *other_idx < idx,
+                    Ordering::Greater => false,
+                })
+                .count();
+            1 + smaller_dividers
+        })
+        .collect();
+    for packet in packets {
+        for (rank, divider) in ranks.iter_mut().zip(dividers) {
+            if packet.packet_order(divider).is_le() {
+                *rank += 1;
+            }
+        }
+    }
+    ranks
+}
+
+/// Packets sorted once, to answer many rank queries.
+#[derive(Debug, Clone)]
+pub struct PacketIndex {
+    sorted: Vec<Data>,
+}
+
+impl PacketIndex {
+    pub fn new(mut packets: Vec<Data>) -> PacketIndex {
+        packets.sort();
+        PacketIndex { sorted: packets }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sorted.len()
+    }
+
+    /// 1-based position the probe would have if it was the only packet added to the index, after
+    /// the packets equivalent to it in the packet order. Uses a binary search.
+    pub fn rank(&self, probe: &Data) -> usize {
+        1 + self.sorted.partition_point(|packet| packet.packet_order(probe).is_le())
+    }
+
+    /// Same as `rank` for each probe. The probes are sorted and the index is walked only once, which
+    /// takes O(probes × log(probes) + packets) comparisons, unless binary searches for each of them
+    /// take fewer.
+    pub fn ranks(&self, probes: &[Data]) -> Vec<usize> {
+        let search_depth = (usize::BITS - self.sorted.len().leading_zeros()) as usize;
+        if probes.len() * search_depth < self.sorted.len() {
+            return probes.iter().map(|probe| self.rank(probe)).collect();
+        }
+        let mut order: Vec<usize> = (0..probes.len()).collect();
+        order.sort_by(|a, b| probes[*a].packet_order(&probes[*b]));
+        let mut ranks = vec![0; probes.len()];
+        let mut smaller = 0;
+        for idx in order {
+            while smaller < self.sorted.len() && self.sorted[smaller].packet_order(&probes[idx]).is_le() {
+                smaller += 1;
+            }
+            ranks[idx] = smaller + 1;
+        }
+        ranks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser_generator::eval_packet;
+
+    fn packets(packets: &[&str]) -> Vec<Data> {
+        packets.iter().map(|p| eval_packet(p).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_divider_ranks() {
+        let input = packets(&["[1,1,3,1,1]", "[1,1,5,1,1]", "[[1],[2,3,4]]", "[[1],4]", "[9]", "[[8,7,6]]", "[[4,4],4,4]",
+            "[[4,4],4,4,4]", "[7,7,7,7]", "[7,7,7]", "[]", "[3]", "[[[]]]", "[[]]", "[1,[2,[3,[4,[5,6,7]]]],8,9]",
+            "[1,[2,[3,[4,[5,6,0]]]],8,9]"]);
+        assert_eq!(divider_ranks(&input, &packets(&["[[2]]", "[[6]]"])), vec![10, 14]);
+        assert_eq!(divider_ranks(&input, &packets(&["[[6]]", "[[2]]"])), vec![14, 10]);
+    }
+
+    #[test]
+    fn test_ties() {
+        let input = packets(&["[2]", "[3]"]);
+        assert_eq!(divider_ranks(&input, &packets(&["[[2]]", "[2]", "[1]"])), vec![3, 4, 1]);
+    }
+
+    #[test]
+    fn test_index() {
+        let index = PacketIndex::new(packets(&["[5]", "[1]", "[3]", "[3]", "[[]]"]));
+        assert_eq!(index.len(), 5);
+        let probes = packets(&["[4]", "[]", "[3]", "[9]", "[[1]]"]);
+        let ranks: Vec<usize> = probes.iter().map(|probe| index.rank(probe)).collect();
+        assert_eq!(ranks, vec![5, 1, 5, 6, 3]);
+        assert_eq!(index.ranks(&probes), ranks);
+        assert_eq!(index.ranks(&probes[..1]), ranks[..1]);
+        for (probe, rank) in probes.iter().zip(ranks) {
+            assert_eq!(divider_ranks(&index.sorted, std::slice::from_ref(probe)), vec![rank]);
+        }
+    }
+}
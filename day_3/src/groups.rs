@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+use std::fmt;
+use crate::{Item, Rucksack};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GroupDetection {
+    /// Groups are made of consecutive rucksacks, in input order.
+    Consecutive,
+    /// Any partition of the rucksacks into groups is acceptable, as long as it is unique.
+    Search,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Group {
+    pub members: Vec<usize>,
+    pub badge: Item,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GroupingError {
+    InvalidGroupSize(usize),
+    IncompleteGroup { rucksacks: usize, group_size: usize },
+    NoBadge { members: Vec<usize> },
+    AmbiguousBadge { members: Vec<usize>, candidates: Vec<Item> },
+    NoPartition,
+    AmbiguousPartition { first: Vec<Group>, second: Vec<Group> },
+}
+
+impl fmt::Display for GroupingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupingError::InvalidGroupSize(size) => write!(f, "Invalid group size {}", size),
+            GroupingError::IncompleteGroup { rucksacks, group_size } =>
+                write!(f, "{} rucksacks cannot be split into groups of {}", rucksacks, group_size),
+            GroupingError::NoBadge { members } =>
+                write!(f, "Group {:?} has no common item", members),
+            GroupingError::AmbiguousBadge { members, candidates } =>
+                write!(f, "Group {:?} has several common items {:?}", members, candidates),
+            GroupingError::NoPartition => write!(f, "No partition into badge groups found"),
+            GroupingError::AmbiguousPartition { .. } => write!(f, "Several partitions into badge groups found"),
+        }
+    }
+}
+
+impl std::error::Error for GroupingError {}
+
+pub fn find_groups(
+    rucksacks: &[Rucksack],
+    group_size: usize,
+    detection: GroupDetection,
+) -> Result<Vec<Group>, GroupingError> {
+    if group_size == 0 {
+        return Err(GroupingError::InvalidGroupSize(group_size));
+    }
+    if !rucksacks.len().is_multiple_of(group_size) {
+        return Err(GroupingError::IncompleteGroup { rucksacks: rucksacks.len(), group_size });
+    }
+    let items: Vec<HashSet<Item>> = rucksacks.iter().map(|r| r.all_items()).collect();
+    match detection {
+        GroupDetection::Consecutive => consecutive_groups(&items, group_size),
+        GroupDetection::Search => search_groups(&items, group_size),
+    }
+}
+
+fn consecutive_groups(items: &[HashSet<Item>], group_size: usize) -> Result<Vec<Group>, GroupingError> {
+    (0..items.len())
+        .step_by(group_size)
+        .map(|start| {
+            let members: Vec<usize> = (start..start + group_size).collect();
+            let mut candidates: Vec<Item> = common_items(items, &members).into_iter().collect();
+            match candidates.len() {
+                0 => Err(GroupingError::NoBadge { members }),
+                1 => Ok(Group { members, badge: candidates[0] }),
+                _ => {
+                    candidates.sort_by_key(|item| item.priority());
+                    Err(GroupingError::AmbiguousBadge { members, candidates })
+                }
+            }
+        })
+        .collect()
+}
+
+fn common_items(items: &[HashSet<Item>], members: &[usize]) -> HashSet<Item> {
+    members.iter()
+        .map(|&idx| items[idx].clone())
+        .reduce(|acc, other| acc.intersection(&other).copied().collect())
+        .unwrap_or_default()
+}
+
+fn search_groups(items: &[HashSet<Item>], group_size: usize) -> Result<Vec<Group>, GroupingError> {
+    let mut search = PartitionSearch {
+        items,
+        group_size,
+        assigned: vec![false; items.len()],
+        current: Vec::new(),
+        found: Vec::new(),
+    };
+    search.next_group();
+    let mut found = search.found.into_iter();
+    match (found.next(), found.next()) {
+        (None, _) => Err(GroupingError::NoPartition),
+        (Some(partition), None) => Ok(partition),
+        (Some(first), Some(second)) => Err(GroupingError::AmbiguousPartition { first, second }),
+    }
+}
+
+/// Backtracking search for partitions, which stops as soon as two have been found: that is enough
+/// to tell a unique partition from an ambiguous one.
+struct PartitionSearch<'a> {
+    items: &'a [HashSet<Item>],
+    group_size: usize,
+    assigned: Vec<bool>,
+    current: Vec<Group>,
+    found: Vec<Vec<Group>>,
+}
+
+impl PartitionSearch<'_> {
+    fn done(&self) -> bool {
+        self.found.len() >= 2
+    }
+
+    fn next_group(&mut self) {
+        // The first unassigned rucksack has to belong to the next group, which avoids enumerating
+        // the same partition in several orders.
+        match self.assigned.iter().position(|assigned| !assigned) {
+            None => self.found.push(self.current.clone()),
+            Some(first) => {
+                self.assigned[first] = true;
+                let common = self.items[first].clone();
+                self.extend_group(vec![first], common);
+                self.assigned[first] = false;
+            }
+        }
+    }
+
+    fn extend_group(&mut self, members: Vec<usize>, common: HashSet<Item>) {
+        if members.len() == self.group_size {
+            if common.len() == 1 {
+                let badge = *common.iter().next().unwrap();
+                self.current.push(Group { members, badge });
+                self.next_group();
+                self.current.pop();
+            }
+            return;
+        }
+        let last = *members.last().unwrap();
+        for candidate in last + 1..self.items.len() {
+            if self.done() {
+                return;
+            }
+            if self.assigned[candidate] {
+                continue;
+            }
+            let narrowed: HashSet<Item> = common.intersection(&self.items[candidate]).copied().collect();
+            if narrowed.is_empty() {
+                continue;
+            }
+            self.assigned[candidate] = true;
+            let mut extended = members.clone();
+            extended.push(candidate);
+            self.extend_group(extended, narrowed);
+            self.assigned[candidate] = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+    use ascii::AsciiChar;
+
+    fn badges(groups: &[Group]) -> String {
+        groups.iter().map(|g| g.badge.0.as_char()).collect()
+    }
+
+    #[test]
+    fn test_consecutive() {
+        let rucksacks = parse_input("vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw").unwrap();
+        let groups = find_groups(&rucksacks, 3, GroupDetection::Consecutive).unwrap();
+        assert_eq!(badges(&groups), "rZ");
+        assert_eq!(groups[1].members, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_consecutive_ambiguous_badge() {
+        let rucksacks = parse_input("abcd\nabef\nabgh").unwrap();
+        let err = find_groups(&rucksacks, 3, GroupDetection::Consecutive).unwrap_err();
+        assert_eq!(
+            err,
+            GroupingError::AmbiguousBadge {
+                members: vec![0, 1, 2],
+                candidates: vec![Item(AsciiChar::a), Item(AsciiChar::b)],
+            }
+        );
+    }
+
+    #[test]
+    fn test_incomplete_group() {
+        let rucksacks = parse_input("ab\ncd").unwrap();
+        assert_eq!(
+            find_groups(&rucksacks, 3, GroupDetection::Search),
+            Err(GroupingError::IncompleteGroup { rucksacks: 2, group_size: 3 })
+        );
+    }
+
+    #[test]
+    fn test_search_unique() {
+        // Groups are interleaved: {0, 2} share 'a' and {1, 3} share 'b'.
+        let rucksacks = parse_input("ax\nby\nza\nbw").unwrap();
+        let groups = find_groups(&rucksacks, 2, GroupDetection::Search).unwrap();
+        assert_eq!(badges(&groups), "ab");
+        assert_eq!(groups[0].members, vec![0, 2]);
+        assert_eq!(groups[1].members, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_search_ambiguous() {
+        let rucksacks = parse_input("ab\nac\nad\nae").unwrap();
+        assert!(matches!(
+            find_groups(&rucksacks, 2, GroupDetection::Search),
+            Err(GroupingError::AmbiguousPartition { .. })
+        ));
+    }
+
+    #[test]
+    fn test_search_impossible() {
+        let rucksacks = parse_input("ab\ncd\nef\ngh").unwrap();
+        assert_eq!(find_groups(&rucksacks, 2, GroupDetection::Search), Err(GroupingError::NoPartition));
+    }
+}
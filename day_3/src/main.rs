@@ -2,18 +2,27 @@ extern crate core;
 
 use std::collections::HashSet;
 use std::error::Error;
-use std::iter::Chain;
-use std::slice::Iter;
 use ascii::{AsAsciiStr, AsciiChar};
-use common::itertools::Itertools;
+use crate::groups::{find_groups, GroupDetection};
+
+mod groups;
 
 static INPUT: &str = include_str!("input");
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
     let rucksacks = parse_input(INPUT)?;
 
-    println!("Part 1 result: {}", first_part(&rucksacks)?);
-    println!("Part 2 result: {}", second_part(&rucksacks)?);
+    match args.as_slice() {
+        [] => {
+            println!("Part 1 result: {}", first_part(&rucksacks)?);
+            println!("Part 2 result: {}", second_part(&rucksacks, GroupDetection::Consecutive)?);
+        }
+        // Groups do not have to be made of consecutive rucksacks, as long as the grouping is unique.
+        ["search"] => println!("Part 2 result: {}", second_part(&rucksacks, GroupDetection::Search)?),
+        _ => Err("Usage: day_3 [search]")?,
+    }
 
     Ok(())
 }
@@ -30,20 +39,9 @@ fn first_part(rucksacks: &[Rucksack]) -> Result<u64, String> {
         .sum()
 }
 
-fn second_part(rucksacks: &[Rucksack]) -> Result<u64, String> {
-    rucksacks.into_iter()
-        .chunks(3)
-        .into_iter()
-        .map(|group| {
-            let common_items =
-                group
-                    .map(|r| r.all_items())
-                    .reduce(|first, second| first.intersection(&second).copied().collect())
-                    .ok_or("Empty group")?;
-            let common_item = common_items.iter().next().ok_or("No intersection found")?;
-            Ok(common_item.priority())
-        })
-        .sum()
+fn second_part(rucksacks: &[Rucksack], detection: GroupDetection) -> Result<u64, String> {
+    let groups = find_groups(rucksacks, 3, detection).map_err(|e| e.to_string())?;
+    Ok(groups.iter().map(|group| group.badge.priority()).sum())
 }
 
 struct Rucksack(Vec<Item>, Vec<Item>);
@@ -112,4 +110,15 @@ CrZsJsPPZsGzwwsLwLmpwMDw";
         let res = first_part(&test_data()).unwrap();
         assert_eq!(res, 157)
     }
+
+    #[test]
+    fn test_2() {
+        let res = second_part(&test_data(), GroupDetection::Consecutive).unwrap();
+        assert_eq!(res, 70);
+        // Other groupings with a single badge each exist, so the search cannot pick one.
+        assert_eq!(
+            second_part(&test_data(), GroupDetection::Search),
+            Err("Several partitions into badge groups found".to_owned())
+        );
+    }
 }
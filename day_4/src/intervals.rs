@@ -0,0 +1,212 @@
+use std::ops::RangeInclusive;
+use crate::Assignment;
+
+/// A set of sections, stored as sorted, disjoint and non-adjacent `from..=to` intervals.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct IntervalSet {
+    intervals: Vec<(u64, u64)>,
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet { intervals: Vec::new() }
+    }
+
+    /// Builds a set from arbitrary ranges, merging the overlapping and adjacent ones.
+    pub fn from_ranges(ranges: impl IntoIterator<Item = RangeInclusive<u64>>) -> IntervalSet {
+        let intervals = ranges.into_iter()
+            .filter(|range| !range.is_empty())
+            .map(|range| (*range.start(), *range.end()))
+            .collect();
+        IntervalSet::merge(intervals)
+    }
+
+    /// Sorts the intervals and merges the ones that overlap or touch each other.
+    fn merge(mut intervals: Vec<(u64, u64)>) -> IntervalSet {
+        intervals.sort_unstable();
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(intervals.len());
+        for (from, to) in intervals {
+            match merged.last_mut() {
+                Some(last) if from <= last.1.saturating_add(1) => last.1 = last.1.max(to),
+                _ => merged.push((from, to)),
+            }
+        }
+        IntervalSet { intervals: merged }
+    }
+
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<u64>> + '_ {
+        self.intervals.iter().map(|&(from, to)| from..=to)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Number of sections in the set, which can be up to 2^64.
+    pub fn coverage(&self) -> u128 {
+        self.intervals.iter().map(|(from, to)| (to - from) as u128 + 1).sum()
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        IntervalSet::merge(self.intervals.iter().chain(other.intervals.iter()).copied().collect())
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a_from, a_to) = self.intervals[i];
+            let (b_from, b_to) = other.intervals[j];
+            let from = a_from.max(b_from);
+            let to = a_to.min(b_to);
+            if from <= to {
+                result.push((from, to));
+            }
+            if a_to < b_to {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { intervals: result }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = Vec::new();
+        let mut j = 0;
+        for &(from, to) in &self.intervals {
+            let mut start = from;
+            while j < other.intervals.len() && other.intervals[j].1 < start {
+                j += 1;
+            }
+            // Whether the end of the interval is left after the cuts.
+            let mut remaining = true;
+            let mut k = j;
+            while k < other.intervals.len() && other.intervals[k].0 <= to {
+                let (cut_from, cut_to) = other.intervals[k];
+                if cut_from > start {
+                    result.push((start, cut_from - 1));
+                }
+                if cut_to >= to {
+                    remaining = false;
+                    break;
+                }
+                start = cut_to + 1;
+                k += 1;
+            }
+            if remaining {
+                result.push((start, to));
+            }
+        }
+        IntervalSet { intervals: result }
+    }
+}
+
+impl From<&Assignment> for IntervalSet {
+    fn from(assignment: &Assignment) -> Self {
+        IntervalSet::from_ranges([assignment.from..=assignment.to])
+    }
+}
+
+/// Number of elves covering each section, as a list of maximal intervals with a constant count.
+/// Built with a sweep line over the assignment bounds, so it runs in O(n log n) for n assignments.
+pub fn coverage_counts<'a>(assignments: impl IntoIterator<Item = &'a Assignment>) -> Vec<(RangeInclusive<u64>, usize)> {
+    let mut events: Vec<(u64, i64)> = Vec::new();
+    for assignment in assignments {
+        if assignment.from <= assignment.to {
+            events.push((assignment.from, 1));
+            // Sections are bounded by u64::MAX, so an assignment ending there never closes.
+            if let Some(end) = assignment.to.checked_add(1) {
+                events.push((end, -1));
+            }
+        }
+    }
+    events.sort_unstable();
+
+    let mut counts = Vec::new();
+    let mut current: i64 = 0;
+    let mut idx = 0;
+    while idx < events.len() {
+        let position = events[idx].0;
+        while idx < events.len() && events[idx].0 == position {
+            current += events[idx].1;
+            idx += 1;
+        }
+        if current > 0 {
+            let end = events.get(idx).map_or(u64::MAX, |(next, _)| next - 1);
+            counts.push((position..=end, current as usize));
+        }
+    }
+    counts
+}
+
+/// Sections covered by at least `n` elves.
+pub fn covered_by_at_least<'a>(assignments: impl IntoIterator<Item = &'a Assignment>, n: usize) -> IntervalSet {
+    IntervalSet::from_ranges(
+        coverage_counts(assignments).into_iter()
+            .filter(|(_, count)| *count >= n)
+            .map(|(range, _)| range)
+    )
+}
+
+/// Sections within `bounds` that no elf covers.
+pub fn uncovered<'a>(assignments: impl IntoIterator<Item = &'a Assignment>, bounds: RangeInclusive<u64>) -> IntervalSet {
+    IntervalSet::from_ranges([bounds]).difference(&covered_by_at_least(assignments, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[RangeInclusive<u64>]) -> IntervalSet {
+        IntervalSet::from_ranges(ranges.iter().cloned())
+    }
+
+    fn assignments() -> Vec<Assignment> {
+        crate::parse_input("2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8").unwrap()
+            .into_iter()
+            .flat_map(|(a, b)| [a, b])
+            .collect()
+    }
+
+    #[test]
+    fn test_merge() {
+        assert_eq!(set(&[5..=7, 1..=2, 3..=3, 6..=9]), set(&[1..=3, 5..=9]));
+        assert_eq!(set(&[1..=3, 4..=5]).ranges().collect::<Vec<_>>(), vec![1..=5]);
+        assert!(IntervalSet::new().is_empty());
+    }
+
+    #[test]
+    fn test_algebra() {
+        let a = set(&[1..=5, 10..=15]);
+        let b = set(&[4..=11, 14..=20]);
+        assert_eq!(a.union(&b), set(&[1..=20]));
+        assert_eq!(a.intersection(&b), set(&[4..=5, 10..=11, 14..=15]));
+        assert_eq!(a.difference(&b), set(&[1..=3, 12..=13]));
+        assert_eq!(b.difference(&a), set(&[6..=9, 16..=20]));
+        assert_eq!(a.coverage(), 11);
+    }
+
+    #[test]
+    fn test_coverage_counts() {
+        let counts = coverage_counts(&assignments());
+        assert_eq!(counts.first(), Some(&(2..=2, 4)));
+        assert_eq!(counts.last(), Some(&(9..=9, 1)));
+        assert_eq!(covered_by_at_least(&assignments(), 6), set(&[4..=7]));
+        assert_eq!(uncovered(&assignments(), 0..=10), set(&[0..=1, 10..=10]));
+    }
+
+    #[test]
+    fn test_full_range() {
+        let everything = uncovered(&[], 0..=u64::MAX);
+        assert_eq!(everything.coverage(), 1 << 64);
+        assert_eq!(everything.difference(&set(&[5..=u64::MAX])), set(&[0..=4]));
+        assert_eq!(set(&[u64::MAX..=u64::MAX]).difference(&set(&[0..=u64::MAX])), IntervalSet::new());
+        assert_eq!(everything.difference(&set(&[0..=9])).coverage(), (1 << 64) - 10);
+    }
+}
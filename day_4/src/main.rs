@@ -3,15 +3,39 @@ extern crate core;
 use common::lazy_static::lazy_static;
 use common::regex::Regex;
 use std::error::Error;
-use std::ops::RangeInclusive;
+use crate::intervals::{coverage_counts, covered_by_at_least, uncovered, IntervalSet};
+
+mod intervals;
 
 static INPUT: &str = include_str!("input");
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
     let assignments = parse_input(INPUT)?;
 
-    println!("Part 1 result: {}", first_part(&assignments));
-    println!("Part 2 result: {}", second_part(&assignments));
+    match args.as_slice() {
+        [] => {
+            println!("Part 1 result: {}", first_part(&assignments));
+            println!("Part 2 result: {}", second_part(&assignments));
+        }
+        ["uncovered"] => println!("Covered by nobody: {}", describe(&uncovered_sections(&assignments))),
+        ["at-least", n] => {
+            let n = n.parse::<usize>().map_err(|_| format!("Invalid elf count {}", n))?;
+            let sections = covered_by_at_least(all_assignments(&assignments), n);
+            println!("Covered by at least {} elves: {}", n, describe(&sections));
+        }
+        ["contested"] => println!("Assigned to both elves of a pair: {}", describe(&contested_sections(&assignments))),
+        ["section", section] => {
+            let section = section.parse::<u64>().map_err(|_| format!("Invalid section {}", section))?;
+            let elves = coverage_counts(all_assignments(&assignments))
+                .into_iter()
+                .find(|(range, _)| range.contains(&section))
+                .map_or(0, |(_, count)| count);
+            println!("Section {} is covered by {} elves", section, elves);
+        }
+        _ => Err("Usage: day_4 [uncovered | at-least <n> | contested | section <id>]")?,
+    }
 
     Ok(())
 }
@@ -32,6 +56,38 @@ fn second_part(assignments: &[AssignmentsPair]) -> usize {
         .count()
 }
 
+fn all_assignments(assignments: &[AssignmentsPair]) -> impl Iterator<Item = &Assignment> {
+    assignments.iter().flat_map(|(assignment_1, assignment_2)| [assignment_1, assignment_2])
+}
+
+/// Sections covered by nobody, between the first and the last assigned ones.
+fn uncovered_sections(assignments: &[AssignmentsPair]) -> IntervalSet {
+    let first = all_assignments(assignments).map(|assignment| assignment.from).min();
+    let last = all_assignments(assignments).map(|assignment| assignment.to).max();
+    match (first, last) {
+        (Some(first), Some(last)) => uncovered(all_assignments(assignments), first..=last),
+        _ => IntervalSet::new(),
+    }
+}
+
+/// Sections assigned to both elves of at least one pair.
+fn contested_sections(assignments: &[AssignmentsPair]) -> IntervalSet {
+    assignments.iter()
+        .map(|(assignment_1, assignment_2)| {
+            IntervalSet::from(assignment_1).intersection(&IntervalSet::from(assignment_2))
+        })
+        .filter(|common| !common.is_empty())
+        .fold(IntervalSet::new(), |contested, common| contested.union(&common))
+}
+
+fn describe(sections: &IntervalSet) -> String {
+    if sections.is_empty() {
+        return "none".to_owned();
+    }
+    let ranges: Vec<String> = sections.ranges().map(|range| format!("{}-{}", range.start(), range.end())).collect();
+    format!("{} ({} sections)", ranges.join(","), sections.coverage())
+}
+
 type AssignmentsPair = (Assignment, Assignment);
 #[derive(Debug, PartialEq)]
 struct Assignment { from: u64, to: u64 }
@@ -40,7 +96,7 @@ impl Assignment {
         self.from <= other.from && self.to >= other.to
     }
     fn overlaps(&self, other: &Assignment) -> bool {
-        (self.to >= other.from && self.from <= other.from) || (self.from <= other.to && self.to >= other.from)
+        self.from <= other.to && other.from <= self.to
     }
 }
 
@@ -103,4 +159,13 @@ mod tests {
     fn test_second_part() {
         assert_eq!(second_part(&test_data()), 4);
     }
+
+    #[test]
+    fn test_section_queries() {
+        assert_eq!(describe(&uncovered_sections(&test_data())), "none");
+        let pairs = parse_input("1-2,8-9\n3-3,3-4").unwrap();
+        assert_eq!(describe(&uncovered_sections(&pairs)), "5-7 (3 sections)");
+        assert_eq!(describe(&contested_sections(&test_data())), "3-7 (5 sections)");
+        assert_eq!(describe(&covered_by_at_least(all_assignments(&test_data()), 6)), "4-7 (4 sections)");
+    }
 }
@@ -0,0 +1,169 @@
+use std::fmt;
+use std::slice::Iter;
+use crate::{Crate, MoveOrder, Stacks};
+
+pub trait Crane {
+    /// Given the crates lifted from the source stack (bottom to top), returns them in the order in
+    /// which they end up on the destination stack (bottom to top).
+    fn arrange(&self, lifted: Vec<Crate>) -> Vec<Crate>;
+}
+
+/// Moves crates one at a time.
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn arrange(&self, mut lifted: Vec<Crate>) -> Vec<Crate> {
+        lifted.reverse();
+        lifted
+    }
+}
+
+/// Moves all the crates at once.
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn arrange(&self, lifted: Vec<Crate>) -> Vec<Crate> {
+        lifted
+    }
+}
+
+/// Moves at most `capacity` crates at once, taking them from the top of the stack each time.
+pub struct LimitedCapacity(pub usize);
+
+impl Crane for LimitedCapacity {
+    fn arrange(&self, lifted: Vec<Crate>) -> Vec<Crate> {
+        // The first batch lifted is the top one, and it ends up at the bottom.
+        lifted.rchunks(self.0.max(1)).flatten().copied().collect()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MoveError {
+    UnknownStack { order: usize, stack: usize },
+    NotEnoughCrates { order: usize, stack: usize, available: usize, requested: u64 },
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::UnknownStack { order, stack } =>
+                write!(f, "Move {}: stack {} not found", order + 1, stack),
+            MoveError::NotEnoughCrates { order, stack, available, requested } =>
+                write!(f, "Move {}: cannot take {} crates from stack {} which has {}", order + 1, requested, stack, available),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// Applies move orders one by one, yielding the state of the stacks after each of them. Stops after
+/// the first invalid order.
+pub struct Executor<'a, C: Crane> {
+    crane: &'a C,
+    stacks: Stacks,
+    orders: Iter<'a, MoveOrder>,
+    executed: usize,
+    failed: bool,
+}
+
+impl<'a, C: Crane> Executor<'a, C> {
+    pub fn new(crane: &'a C, init_stacks: &Stacks, orders: &'a [MoveOrder]) -> Executor<'a, C> {
+        Executor { crane, stacks: init_stacks.clone(), orders: orders.iter(), executed: 0, failed: false }
+    }
+
+    fn validate(&self, order: &MoveOrder) -> Result<(), MoveError> {
+        let idx = self.executed;
+        for stack in [order.from, order.to] {
+            if stack == 0 || stack > self.stacks.all.len() {
+                return Err(MoveError::UnknownStack { order: idx, stack });
+            }
+        }
+        let available = self.stacks.all[order.from - 1].len();
+        if order.qty > available as u64 {
+            return Err(MoveError::NotEnoughCrates { order: idx, stack: order.from, available, requested: order.qty });
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, order: &MoveOrder) {
+        let from = &mut self.stacks.all[order.from - 1];
+        let lifted = from.split_off(from.len() - order.qty as usize);
+        let placed = self.crane.arrange(lifted);
+        self.stacks.all[order.to - 1].extend(placed);
+    }
+}
+
+impl<C: Crane> Iterator for Executor<'_, C> {
+    type Item = Result<Stacks, MoveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let order = self.orders.next()?;
+        if let Err(e) = self.validate(order) {
+            self.failed = true;
+            return Some(Err(e));
+        }
+        self.execute(order);
+        self.executed += 1;
+        Some(Ok(self.stacks.clone()))
+    }
+}
+
+/// Applies all the move orders and returns the final state of the stacks.
+pub fn apply_moves(crane: &impl Crane, init_stacks: &Stacks, orders: &[MoveOrder]) -> Result<Stacks, MoveError> {
+    let mut stacks = init_stacks.clone();
+    for state in Executor::new(crane, init_stacks, orders) {
+        stacks = state?;
+    }
+    Ok(stacks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crates(s: &str) -> Vec<Crate> {
+        s.chars().map(Crate).collect()
+    }
+
+    #[test]
+    fn test_arrange() {
+        assert_eq!(CrateMover9000.arrange(crates("ABCDE")), crates("EDCBA"));
+        assert_eq!(CrateMover9001.arrange(crates("ABCDE")), crates("ABCDE"));
+        assert_eq!(LimitedCapacity(2).arrange(crates("ABCDE")), crates("DEBCA"));
+        assert_eq!(LimitedCapacity(1).arrange(crates("ABCDE")), crates("EDCBA"));
+        assert_eq!(LimitedCapacity(5).arrange(crates("ABCDE")), crates("ABCDE"));
+    }
+
+    #[test]
+    fn test_executor_steps() {
+        let stacks = Stacks { all: vec![crates("ZN"), crates("MCD"), crates("P")] };
+        let orders = vec![MoveOrder { qty: 1, from: 2, to: 1 }, MoveOrder { qty: 3, from: 1, to: 3 }];
+        let states: Vec<Stacks> = Executor::new(&CrateMover9000, &stacks, &orders)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(states[0], Stacks { all: vec![crates("ZND"), crates("MC"), crates("P")] });
+        assert_eq!(states[1], Stacks { all: vec![crates(""), crates("MC"), crates("PDNZ")] });
+    }
+
+    #[test]
+    fn test_executor_errors() {
+        let stacks = Stacks { all: vec![crates("ZN"), crates("MCD")] };
+        let orders = vec![
+            MoveOrder { qty: 1, from: 2, to: 1 },
+            MoveOrder { qty: 4, from: 1, to: 2 },
+            MoveOrder { qty: 1, from: 1, to: 2 },
+        ];
+        let results: Vec<_> = Executor::new(&CrateMover9001, &stacks, &orders).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1], Err(MoveError::NotEnoughCrates { order: 1, stack: 1, available: 3, requested: 4 }));
+
+        let orders = vec![MoveOrder { qty: 1, from: 1, to: 3 }];
+        assert_eq!(
+            apply_moves(&CrateMover9001, &stacks, &orders),
+            Err(MoveError::UnknownStack { order: 0, stack: 3 })
+        );
+    }
+}
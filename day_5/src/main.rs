@@ -3,53 +3,64 @@ extern crate core;
 use common::lazy_static::lazy_static;
 use common::regex::Regex;
 use std::error::Error;
-use std::fmt::format;
+use crate::cranes::{apply_moves, CrateMover9000, CrateMover9001, LimitedCapacity};
+use crate::planner::find_moves;
 
-static INPUT_1: &str = include_str!("input_1");
-static INPUT_2: &str = include_str!("input_2");
+mod cranes;
+mod planner;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let stacks = stacks();
-    let moves = parse_moves(INPUT_2)?;
+static INPUT: &str = include_str!("input");
 
-    println!("Part 1 result: {}", first_part(&stacks, &moves)?);
-    println!("Part 2 result: {}", second_part(&stacks, &moves)?);
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    let (stacks, moves) = parse_input(INPUT)?;
+
+    match args.as_slice() {
+        [] => {
+            println!("Part 1 result: {}", first_part(&stacks, &moves)?);
+            println!("Part 2 result: {}", second_part(&stacks, &moves)?);
+        }
+        ["capacity", capacity] => {
+            let capacity = capacity.parse::<usize>().map_err(|_| format!("Invalid capacity {}", capacity))?;
+            println!("Top crates: {}", limited_capacity(&stacks, &moves, capacity)?);
+        }
+        ["plan", crane, target, rest @ ..] => {
+            let max_moves = match rest {
+                [] => 2,
+                [max_moves] => max_moves.parse::<usize>().map_err(|_| format!("Invalid move count {}", max_moves))?,
+                _ => Err("Usage: day_5 plan <9000 | 9001> <target> [max moves]")?,
+            };
+            let plan = match *crane {
+                "9000" => find_moves(&CrateMover9000, &stacks, target, max_moves).map_err(|e| e.to_string())?,
+                "9001" => find_moves(&CrateMover9001, &stacks, target, max_moves).map_err(|e| e.to_string())?,
+                _ => Err(format!("Unknown crane CrateMover {}", crane))?,
+            };
+            if plan.is_empty() {
+                println!("The top crates already spell {}", target);
+            }
+            for order in plan {
+                println!("move {} from {} to {}", order.qty, order.from, order.to);
+            }
+        }
+        _ => Err("Usage: day_5 [capacity <crates> | plan <9000 | 9001> <target> [max moves]]")?,
+    }
 
     Ok(())
 }
 
 fn first_part(init_stacks: &Stacks, move_orders: &[MoveOrder]) -> Result<String, String> {
-    fn apply_moves(stacks: &mut Stacks, orders: &[MoveOrder]) -> Result<(), String> {
-        for order in orders {
-            for _ in 0..order.qty {
-                let c = stacks.get_mut(order.from)?.pop().ok_or("from_stack empty")?;
-                stacks.get_mut(order.to)?.push(c);
-            }
-        }
-        Ok(())
-    }
-
-    let mut stacks = init_stacks.clone();
-    apply_moves(&mut stacks, move_orders)?;
+    let stacks = apply_moves(&CrateMover9000, init_stacks, move_orders).map_err(|e| e.to_string())?;
     Ok(top_crates(&stacks))
 }
 
 fn second_part(init_stacks: &Stacks, move_orders: &[MoveOrder]) -> Result<String, String> {
-    fn apply_moves(stacks: &mut Stacks, orders: &[MoveOrder]) -> Result<(), String> {
-        for order in orders {
-            let mut crates = vec![];
-            for _ in 0..order.qty {
-                crates.push(stacks.get_mut(order.from)?.pop().ok_or("from_stack empty")?);
-            }
-            for c in crates.iter().rev() {
-                stacks.get_mut(order.to)?.push(*c);
-            }
-        }
-        Ok(())
-    }
+    let stacks = apply_moves(&CrateMover9001, init_stacks, move_orders).map_err(|e| e.to_string())?;
+    Ok(top_crates(&stacks))
+}
 
-    let mut stacks = init_stacks.clone();
-    apply_moves(&mut stacks, move_orders)?;
+fn limited_capacity(init_stacks: &Stacks, move_orders: &[MoveOrder], capacity: usize) -> Result<String, String> {
+    let stacks = apply_moves(&LimitedCapacity(capacity), init_stacks, move_orders).map_err(|e| e.to_string())?;
     Ok(top_crates(&stacks))
 }
 
@@ -61,43 +72,65 @@ fn top_crates(stacks: &Stacks) -> String {
         )
 }
 
-#[derive(Clone, Copy)]
-struct Crate(char);
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Crate(char);
 
-#[derive(Clone)]
-struct Stacks {
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Stacks {
     all: Vec<Vec<Crate>>
 }
 
-impl Stacks {
-    fn get_mut(&mut self, idx: usize) -> Result<&mut Vec<Crate>, String> {
-        self.all.get_mut(idx - 1).ok_or(format!("Stack {} not found", idx))
-    }
-}
-
-#[derive(Debug, PartialEq)]
-struct MoveOrder {
+#[derive(Debug, PartialEq, Clone)]
+pub struct MoveOrder {
     qty: u64,
     from: usize,
     to: usize
 }
 
-fn stacks() -> Stacks {
-    let chars = vec![
-        vec!['G', 'D', 'V', 'Z', 'J', 'S', 'B'],
-        vec!['Z', 'S', 'M', 'G', 'V', 'P'],
-        vec!['C', 'L', 'B', 'S', 'W', 'T', 'Q', 'F'],
-        vec!['H', 'J', 'G', 'W', 'M', 'R', 'V', 'Q'],
-        vec!['C', 'L', 'S', 'N', 'F', 'M', 'D'],
-        vec!['R', 'G', 'C', 'D'],
-        vec!['H', 'G', 'T', 'R', 'J', 'D', 'S', 'Q'],
-        vec!['P', 'F', 'V'],
-        vec!['D', 'R', 'S', 'T', 'J'],
-    ];
-    Stacks {
-        all: chars.iter().map(|s| s.iter().map(|c| Crate(*c)).collect()).collect()
+fn parse_input(input: &str) -> Result<(Stacks, Vec<MoveOrder>), String> {
+    let normalized = input.replace("\r\n", "\n");
+    let (drawing, moves) = normalized
+        .split_once("\n\n")
+        .ok_or("Missing blank line between the stacks drawing and the moves")?;
+    Ok((parse_stacks(drawing)?, parse_moves(moves)?))
+}
+
+fn parse_stacks(drawing: &str) -> Result<Stacks, String> {
+    let mut lines = drawing.lines().rev().skip_while(|line| line.trim().is_empty());
+    let index_row = lines.next().ok_or("Empty stacks drawing")?;
+    let indices = index_row
+        .split_whitespace()
+        .map(|idx| idx.parse::<usize>().map_err(|_| format!("Invalid stack index {}", idx)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if indices.iter().enumerate().any(|(i, idx)| *idx != i + 1) {
+        return Err(format!("Stack indices should be 1 to {}: {}", indices.len(), index_row));
     }
 
+    let mut all = vec![Vec::new(); indices.len()];
+    // Lines are read from the bottom up, so once a stack has a gap, nothing can be stacked on it.
+    let mut closed = vec![false; indices.len()];
+    for line in lines {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.len() > indices.len() * 4 {
+            return Err(format!("Too many crates in line {}", line));
+        }
+        for (idx, cell) in chars.chunks(4).enumerate() {
+            match cell {
+                _ if cell.iter().all(|c| *c == ' ') => closed[idx] = true,
+                ['[', c, ']', ..] if c.is_ascii_alphabetic() => {
+                    if closed[idx] {
+                        return Err(format!("Floating crate {} in stack {}", c, idx + 1));
+                    }
+                    all[idx].push(Crate(*c));
+                }
+                _ => return Err(format!("Failed to parse crate {} in line {}", idx + 1, line)),
+            }
+        }
+        for stack_closed in closed.iter_mut().skip(chars.len().div_ceil(4)) {
+            *stack_closed = true;
+        }
+    }
+    Ok(Stacks { all })
 }
 
 fn parse_moves(
@@ -126,22 +159,42 @@ fn parse_moves(
 mod tests {
     use super::*;
 
-    fn test_moves() -> Vec<MoveOrder> {
-        let test_input = "move 1 from 2 to 1
+    static TEST_INPUT: &str = "    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
 move 3 from 1 to 3
 move 2 from 2 to 1
 move 1 from 1 to 2";
-        parse_moves(test_input).unwrap()
+
+    fn test_moves() -> Vec<MoveOrder> {
+        parse_input(TEST_INPUT).unwrap().1
     }
 
     fn test_stacks() -> Stacks {
-        Stacks {
+        parse_input(TEST_INPUT).unwrap().0
+    }
+
+    #[test]
+    fn test_parse_stacks() {
+        let expected = Stacks {
             all: vec![
                 vec![Crate('Z'), Crate('N')],
                 vec![Crate('M'), Crate('C'), Crate('D')],
                 vec![Crate('P')],
             ]
-        }
+        };
+        assert_eq!(test_stacks(), expected);
+    }
+
+    #[test]
+    fn test_parse_stacks_errors() {
+        assert!(parse_stacks("[A]     [B]\n 1   2").is_err());
+        assert!(parse_stacks("    [A]\n        \n[B] [C]\n 1   2").is_err());
+        assert!(parse_stacks("[A] [B]\n 1   3").is_err());
+        assert!(parse_input("[A]\n 1\nmove 1 from 1 to 1").is_err());
     }
 
     #[test]
@@ -168,4 +221,12 @@ move 1 from 1 to 2";
         assert_eq!(actual, "MCD");
         Ok(())
     }
+
+    #[test]
+    fn test_limited_capacity() -> Result<(), String> {
+        assert_eq!(limited_capacity(&test_stacks(), &test_moves(), 1)?, "CMZ");
+        assert_eq!(limited_capacity(&test_stacks(), &test_moves(), 2)?, "MCZ");
+        assert_eq!(limited_capacity(&test_stacks(), &test_moves(), 3)?, "MCD");
+        Ok(())
+    }
 }
@@ -0,0 +1,148 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use crate::cranes::{apply_moves, Crane};
+use crate::{top_crates, MoveOrder, Stacks};
+
+#[derive(Debug, PartialEq)]
+pub enum PlanError {
+    /// Every arrangement reachable from the initial stacks was explored without finding the target.
+    Unreachable,
+    /// No plan of at most `bound` moves exists, but longer plans were not explored.
+    NotWithin { bound: usize },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::Unreachable => write!(f, "The target arrangement is unreachable"),
+            PlanError::NotWithin { bound } => write!(f, "No plan found within {} moves", bound),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+/// Breadth-first search for a shortest sequence of moves, with the given crane, after which the top
+/// crates of the stacks spell out `target`.
+pub fn find_moves(
+    crane: &impl Crane,
+    init_stacks: &Stacks,
+    target: &str,
+    max_moves: usize,
+) -> Result<Vec<MoveOrder>, PlanError> {
+    if !has_crates_for(init_stacks, target) {
+        return Err(PlanError::Unreachable);
+    }
+    // Each visited arrangement is mapped to the arrangement it was reached from, and the move used.
+    let mut parents: HashMap<Stacks, Option<(Stacks, MoveOrder)>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    parents.insert(init_stacks.clone(), None);
+    queue.push_back((init_stacks.clone(), 0));
+
+    let mut truncated = false;
+    while let Some((stacks, depth)) = queue.pop_front() {
+        if top_crates(&stacks) == target {
+            return Ok(rebuild_plan(&parents, stacks));
+        }
+        if depth == max_moves {
+            truncated = true;
+            continue;
+        }
+        for order in possible_moves(&stacks) {
+            let next = apply_moves(crane, &stacks, std::slice::from_ref(&order))
+                .expect("possible_moves only generates valid moves");
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some((stacks.clone(), order)));
+                queue.push_back((next, depth + 1));
+            }
+        }
+    }
+    if truncated {
+        Err(PlanError::NotWithin { bound: max_moves })
+    } else {
+        Err(PlanError::Unreachable)
+    }
+}
+
+/// Whether the stacks hold every crate of `target`, and enough stacks to put each of them on top.
+/// Moves never create nor remove crates, so the search cannot succeed otherwise.
+fn has_crates_for(stacks: &Stacks, target: &str) -> bool {
+    let mut available: HashMap<char, usize> = HashMap::new();
+    for crate_ in stacks.all.iter().flatten() {
+        *available.entry(crate_.0).or_default() += 1;
+    }
+    target.chars().count() <= stacks.all.len()
+        && target.chars().all(|c| match available.get_mut(&c) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        })
+}
+
+fn possible_moves(stacks: &Stacks) -> Vec<MoveOrder> {
+    let count = stacks.all.len();
+    let mut moves = Vec::new();
+    for from in 1..=count {
+        for to in (1..=count).filter(|to| *to != from) {
+            for qty in 1..=stacks.all[from - 1].len() as u64 {
+                moves.push(MoveOrder { qty, from, to });
+            }
+        }
+    }
+    moves
+}
+
+fn rebuild_plan(parents: &HashMap<Stacks, Option<(Stacks, MoveOrder)>>, last: Stacks) -> Vec<MoveOrder> {
+    let mut plan = Vec::new();
+    let mut current = last;
+    while let Some(Some((previous, order))) = parents.get(&current) {
+        plan.push(order.clone());
+        current = previous.clone();
+    }
+    plan.reverse();
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cranes::{CrateMover9000, CrateMover9001};
+    use crate::Crate;
+
+    fn stacks(all: &[&str]) -> Stacks {
+        Stacks { all: all.iter().map(|s| s.chars().map(Crate).collect()).collect() }
+    }
+
+    #[test]
+    fn test_find_moves() {
+        let init = stacks(&["ZN", "MCD", "P"]);
+
+        let plan = find_moves(&CrateMover9000, &init, "CMZ", 10).unwrap();
+        let result = apply_moves(&CrateMover9000, &init, &plan).unwrap();
+        assert_eq!(top_crates(&result), "CMZ");
+        assert_eq!(plan.len(), 2);
+
+        let plan = find_moves(&CrateMover9001, &init, "DCP", 10).unwrap();
+        assert_eq!(plan, vec![MoveOrder { qty: 1, from: 2, to: 1 }]);
+    }
+
+    #[test]
+    fn test_already_arranged() {
+        let init = stacks(&["ZN", "MCD", "P"]);
+        assert_eq!(find_moves(&CrateMover9000, &init, "NDP", 0), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_no_plan() {
+        let init = stacks(&["ZN", "MCD", "P"]);
+        assert_eq!(find_moves(&CrateMover9000, &init, "MZP", 1), Err(PlanError::NotWithin { bound: 1 }));
+        assert_eq!(find_moves(&CrateMover9000, &init, "XYZ", 100), Err(PlanError::Unreachable));
+        assert_eq!(find_moves(&CrateMover9000, &init, "ZZ", 100), Err(PlanError::Unreachable));
+
+        // A single stack leaves no move to make, so the search ends right away.
+        let single = stacks(&["AB"]);
+        assert_eq!(find_moves(&CrateMover9000, &single, "A", 100), Err(PlanError::Unreachable));
+    }
+}
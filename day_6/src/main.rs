@@ -1,30 +1,54 @@
 extern crate core;
 
-use std::collections::HashSet;
 use std::error::Error;
+use std::fs::File;
+use std::io;
+use crate::markers::{scan_markers, MarkerDetector};
+
+mod markers;
 
 static INPUT: &str = include_str!("input");
 
 fn main() -> Result<(), Box<dyn Error>> {
-    println!("Part 1 result: {}", find_marker(INPUT, 4)?);
-    println!("Part 2 result: {}", find_marker(INPUT, 14)?);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+
+    match args.as_slice() {
+        [] => {
+            println!("Part 1 result: {}", find_marker(INPUT, 4)?);
+            println!("Part 2 result: {}", find_marker(INPUT, 14)?);
+        }
+        ["scan", path, lengths @ ..] if !lengths.is_empty() => {
+            let lengths = lengths.iter()
+                .map(|length| length.parse::<usize>().map_err(|_| format!("Invalid marker length {}", length)))
+                .collect::<Result<Vec<_>, _>>()?;
+            // `-` scans the standard input, so that captures can be piped in.
+            let markers = match *path {
+                "-" => scan_markers(io::stdin().lock(), &lengths)?,
+                _ => scan_markers(File::open(path)?, &lengths)?,
+            };
+            for (length, offsets) in markers {
+                match offsets.first() {
+                    Some(first) => println!("Length {}: {} markers, first after {} bytes", length, offsets.len(), first),
+                    None => println!("Length {}: no marker", length),
+                }
+            }
+        }
+        _ => Err("Usage: day_6 [scan <file | -> <length>...]")?,
+    }
 
     Ok(())
 }
 
 fn find_marker(buffer: &str, length: usize) -> Result<usize, String> {
-    let chars: Vec<_> = buffer.char_indices().collect();
-    chars
-        .windows(length)
-        .find_map(|window| {
-            let set: HashSet<_> = window.iter().map(|(_, c)| c).collect();
-            if set.len() == length {
-                window.last().map(|(idx, _)| idx + 1)
-            } else {
-                None
-            }
-        })
-        .ok_or(format!("Marker of length {} not found", length))
+    let mut detector = MarkerDetector::new(&[length])?;
+    for byte in buffer.bytes() {
+        detector.push(byte);
+        if let Some(marker) = detector.first_marker(length) {
+            return Ok(marker);
+        }
+    }
+    Err(format!("Marker of length {} not found", length))
 }
 
 #[cfg(test)]
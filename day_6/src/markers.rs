@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{ErrorKind, Read};
+
+/// Finds markers (windows of distinct bytes) of several lengths in a single pass over a stream.
+/// Each window keeps a frequency table that is updated incrementally as bytes enter and leave it,
+/// so every byte is processed in constant time per window length.
+pub struct MarkerDetector {
+    windows: Vec<Window>,
+    history: Vec<u8>,
+    position: usize,
+}
+
+struct Window {
+    length: usize,
+    counts: [u32; 256],
+    distinct: usize,
+    markers: Vec<usize>,
+}
+
+impl MarkerDetector {
+    pub fn new(lengths: &[usize]) -> Result<MarkerDetector, String> {
+        if let Some(length) = lengths.iter().find(|length| **length == 0 || **length > 256) {
+            return Err(format!("Invalid marker length {}", length));
+        }
+        let windows = lengths.iter()
+            .map(|&length| Window { length, counts: [0; 256], distinct: 0, markers: Vec::new() })
+            .collect();
+        let history_len = lengths.iter().copied().max().unwrap_or(1);
+        Ok(MarkerDetector { windows, history: vec![0; history_len], position: 0 })
+    }
+
+    pub fn push(&mut self, byte: u8) {
+        let history_len = self.history.len();
+        for window in self.windows.iter_mut() {
+            if self.position >= window.length {
+                // The ring buffer still holds this byte, since no window is longer than it.
+                let outgoing = self.history[(self.position - window.length) % history_len];
+                window.counts[outgoing as usize] -= 1;
+                if window.counts[outgoing as usize] == 0 {
+                    window.distinct -= 1;
+                }
+            }
+            window.counts[byte as usize] += 1;
+            if window.counts[byte as usize] == 1 {
+                window.distinct += 1;
+            }
+            if window.distinct == window.length {
+                window.markers.push(self.position + 1);
+            }
+        }
+        self.history[self.position % history_len] = byte;
+        self.position += 1;
+    }
+
+    /// Marker offsets found so far, for each window length. An offset is the number of bytes read
+    /// when the marker is complete.
+    pub fn markers(&self) -> BTreeMap<usize, Vec<usize>> {
+        self.windows.iter()
+            .map(|window| (window.length, window.markers.clone()))
+            .collect()
+    }
+
+    pub fn first_marker(&self, length: usize) -> Option<usize> {
+        self.windows.iter()
+            .find(|window| window.length == length)
+            .and_then(|window| window.markers.first().copied())
+    }
+}
+
+pub fn scan_markers<R: Read>(mut reader: R, lengths: &[usize]) -> Result<BTreeMap<usize, Vec<usize>>, Box<dyn Error>> {
+    let mut detector = MarkerDetector::new(lengths)?;
+    let mut buffer = [0u8; 8192];
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for byte in &buffer[..read] {
+            detector.push(*byte);
+        }
+    }
+    Ok(detector.markers())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_markers() {
+        let markers = scan_markers("mjqjpqmgbljsphdztnvjfqwrcgsmlb".as_bytes(), &[4, 14]).unwrap();
+        assert_eq!(markers[&4][..4], [7, 8, 9, 10]);
+        assert_eq!(markers[&14].first(), Some(&19));
+    }
+
+    #[test]
+    fn test_chunked_stream() {
+        let input: Vec<u8> = "abcabcabc".repeat(2000).bytes().chain("xyzw".bytes()).collect();
+        let markers = scan_markers(&input[..], &[3, 4, 5]).unwrap();
+        assert_eq!(markers[&3].len(), 17998 + 4);
+        assert_eq!(markers[&4], vec![18001, 18002, 18003, 18004]);
+        assert_eq!(markers[&5], vec![18002, 18003, 18004]);
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        assert!(MarkerDetector::new(&[4, 0]).is_err());
+    }
+}
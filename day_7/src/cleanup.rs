@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use crate::fs::{FileSystem, NodeId, ROOT};
+
+#[derive(Debug, PartialEq)]
+pub struct CleanupPlan {
+    /// Paths and sizes of the directories to delete, none of them inside another one.
+    pub dirs: Vec<(String, u64)>,
+    pub freed: u64,
+    pub must_be_freed: u64,
+    pub free_before: u64,
+    /// Smallest single directory which would free enough space, for comparison.
+    pub single_dir: Option<(String, u64)>,
+}
+
+impl CleanupPlan {
+    pub fn explain(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "{} free, {} must be freed", self.free_before, self.must_be_freed).unwrap();
+        if self.dirs.is_empty() {
+            writeln!(out, "Nothing to delete").unwrap();
+            return out;
+        }
+        for (path, size) in &self.dirs {
+            writeln!(out, "Delete {} ({})", path, size).unwrap();
+        }
+        writeln!(
+            out,
+            "Frees {}, which is {} more than needed",
+            self.freed,
+            self.freed - self.must_be_freed
+        ).unwrap();
+        match &self.single_dir {
+            Some((path, size)) if *size > self.freed => writeln!(
+                out,
+                "Deleting only {} ({}) would free {} more",
+                path,
+                size,
+                size - self.freed
+            ).unwrap(),
+            Some(_) => writeln!(out, "No combination of directories beats the smallest sufficient one").unwrap(),
+            None => writeln!(out, "No single directory is large enough").unwrap(),
+        }
+        out
+    }
+}
+
+/// Finds the set of directories, none of them nested in another one, whose deletion frees at
+/// least the required space with the smallest total size.
+///
+/// This is a knapsack over the directories in preorder: at each directory, we either keep it and
+/// move on to the next one, or delete it and skip its whole subtree. Only the amounts below the
+/// target which can actually be freed are stored, each with the last directory deleted to free it,
+/// so this runs in O(directories × amounts) time and O(amounts) memory.
+pub fn plan_cleanup(fs: &FileSystem, disk_size: u64, required_free: u64) -> Result<CleanupPlan, String> {
+    let sizes = fs.du();
+    let free_before = disk_size.saturating_sub(sizes[ROOT]);
+    let must_be_freed = required_free.saturating_sub(free_before);
+
+    let single_dir = (1..fs.len())
+        .filter(|id| fs.is_dir(*id) && sizes[*id] >= must_be_freed)
+        .min_by_key(|id| sizes[*id])
+        .map(|id| (fs.path(id), sizes[id]));
+
+    if must_be_freed == 0 {
+        return Ok(CleanupPlan { dirs: vec![], freed: 0, must_be_freed, free_before, single_dir });
+    }
+
+    // The root directory itself cannot be deleted, only its content.
+    let (order, ends) = preorder_dirs(fs);
+    // An amount freed by deleting the directory at some position can be increased by deleting the
+    // directories after its subtree only.
+    let available_from = |last_deleted: Option<usize>| last_deleted.map_or(0, |pos| ends[pos]);
+    let mut last_deleted: HashMap<u64, Option<usize>> = HashMap::from([(0, None)]);
+    let mut reachable: Vec<u64> = Vec::new();
+    let mut arrivals: Vec<Vec<u64>> = vec![Vec::new(); order.len() + 1];
+    arrivals[0].push(0);
+    // Best solution so far: (freed, position of the last deleted directory, amount before it).
+    let mut best: Option<(u64, usize, u64)> = None;
+    for (pos, id) in order.iter().enumerate() {
+        for amount in std::mem::take(&mut arrivals[pos]) {
+            // An amount can arrive several times, if it was later found to be available sooner.
+            if available_from(last_deleted[&amount]) == pos {
+                reachable.push(amount);
+            }
+        }
+        let size = sizes[*id];
+        if size == 0 {
+            continue;
+        }
+        for &before in &reachable {
+            let freed = before + size;
+            if freed >= must_be_freed {
+                if best.is_none_or(|(best_freed, _, _)| freed < best_freed) {
+                    best = Some((freed, pos, before));
+                }
+            } else if last_deleted.get(&freed).is_none_or(|last| available_from(*last) > ends[pos]) {
+                last_deleted.insert(freed, Some(pos));
+                arrivals[ends[pos]].push(freed);
+            }
+        }
+    }
+    let (freed, last, before) =
+        best.ok_or(format!("Cannot free {} even by deleting every directory", must_be_freed))?;
+
+    // Walk back through the directories deleted to free each amount, down to nothing.
+    let mut deleted = vec![order[last]];
+    let mut amount = before;
+    while let Some(pos) = last_deleted[&amount] {
+        deleted.push(order[pos]);
+        amount -= sizes[order[pos]];
+    }
+    let mut dirs: Vec<(String, u64)> = deleted.into_iter().map(|id| (fs.path(id), sizes[id])).collect();
+    dirs.sort();
+    Ok(CleanupPlan { dirs, freed, must_be_freed, free_before, single_dir })
+}
+
+/// Directories below the root in preorder, along with the position right after each subtree.
+fn preorder_dirs(fs: &FileSystem) -> (Vec<NodeId>, Vec<usize>) {
+    fn visit(fs: &FileSystem, id: NodeId, order: &mut Vec<NodeId>, ends: &mut Vec<usize>) {
+        let pos = order.len();
+        order.push(id);
+        ends.push(0);
+        for child in fs.children(id).filter(|child| fs.is_dir(*child)) {
+            visit(fs, child, order, ends);
+        }
+        ends[pos] = order.len();
+    }
+
+    let mut order = Vec::new();
+    let mut ends = Vec::new();
+    for child in fs.children(ROOT).filter(|child| fs.is_dir(*child)) {
+        visit(fs, child, &mut order, &mut ends);
+    }
+    (order, ends)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TEST_INPUT: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    #[test]
+    fn test_single_dir() {
+        let fs = FileSystem::from_transcript(TEST_INPUT).unwrap();
+        let plan = plan_cleanup(&fs, 70000000, 30000000).unwrap();
+        assert_eq!(plan.must_be_freed, 8381165);
+        assert_eq!(plan.dirs, vec![("/d".to_owned(), 24933642)]);
+        assert_eq!(plan.single_dir, Some(("/d".to_owned(), 24933642)));
+    }
+
+    #[test]
+    fn test_several_dirs() {
+        let fs = FileSystem::from_transcript("$ cd /
+$ ls
+dir a
+dir b
+dir c
+$ cd a
+$ ls
+dir x
+dir y
+$ cd x
+$ ls
+40 f
+$ cd ..
+$ cd y
+$ ls
+35 f
+$ cd /
+$ cd b
+$ ls
+30 f
+$ cd /
+$ cd c
+$ ls
+100 f").unwrap();
+        // 205 used out of 250, so 55 must be freed: /a/y and /b are better than /a or /c.
+        let plan = plan_cleanup(&fs, 250, 100).unwrap();
+        assert_eq!(plan.free_before, 45);
+        assert_eq!(plan.dirs, vec![("/a/y".to_owned(), 35), ("/b".to_owned(), 30)]);
+        assert_eq!(plan.freed, 65);
+        assert_eq!(plan.single_dir, Some(("/a".to_owned(), 75)));
+        assert!(plan.explain().contains("Deleting only /a (75) would free 10 more"));
+    }
+
+    #[test]
+    fn test_many_empty_dirs_and_a_large_file() {
+        let mut transcript = "$ cd /\n$ ls\ndir big\n".to_owned();
+        for i in 0..1000 {
+            transcript.push_str(&format!("dir empty_{}\n", i));
+        }
+        transcript.push_str("$ cd big\n$ ls\n50000000 f");
+        let fs = FileSystem::from_transcript(&transcript).unwrap();
+        let plan = plan_cleanup(&fs, 50000000, 50000000).unwrap();
+        assert_eq!(plan.dirs, vec![("/big".to_owned(), 50000000)]);
+        assert!(plan_cleanup(&fs, 50000000, 60000000).is_err());
+    }
+
+    #[test]
+    fn test_nothing_to_do_or_impossible() {
+        let fs = FileSystem::from_transcript(TEST_INPUT).unwrap();
+        assert!(plan_cleanup(&fs, 100000000, 30000000).unwrap().dirs.is_empty());
+        assert!(plan_cleanup(&fs, 48381165, 30000000).is_err());
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+pub type NodeId = usize;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NodeKind {
+    /// `listed` is false for directories we only know about through `dir` entries or `cd` commands.
+    Dir { children: BTreeMap<String, NodeId>, listed: bool },
+    File { size: u64 },
+}
+
+/// File system tree stored in an arena: nodes refer to their parent and children by index, and a
+/// node is always created after its parent.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+}
+
+pub const ROOT: NodeId = 0;
+
+impl FileSystem {
+    pub fn new() -> FileSystem {
+        let root = Node {
+            name: "/".to_owned(),
+            parent: None,
+            kind: NodeKind::Dir { children: BTreeMap::new(), listed: false },
+        };
+        FileSystem { nodes: vec![root] }
+    }
+
+    /// Replays a terminal transcript. Changing into a directory which was never listed creates it.
+    pub fn from_transcript(input: &str) -> Result<FileSystem, String> {
+        let mut fs = FileSystem::new();
+        let mut current = ROOT;
+        for line in input.lines() {
+            if line.starts_with("$ ls") {
+                fs.mark_listed(current);
+            } else if let Some(target) = line.strip_prefix("$ cd ") {
+                current = match target.trim() {
+                    "/" => ROOT,
+                    ".." => fs.nodes[current].parent.unwrap_or(ROOT),
+                    name => fs.get_or_create_dir(current, name)
+                        .ok_or(format!("Cannot cd into file '{}'", fs.path_to(current, name)))?,
+                };
+            } else if line.starts_with('$') {
+                return Err(format!("Invalid: '{}'", line));
+            } else {
+                let (res_type, name) = line.split_once(' ').ok_or(format!("Invalid: '{}'", line))?;
+                if res_type == "dir" {
+                    fs.get_or_create_dir(current, name)
+                        .ok_or(format!("'{}' is both a file and a directory", fs.path_to(current, name)))?;
+                } else {
+                    let size = res_type.parse::<u64>().map_err(|_| format!("Invalid: '{}'", line))?;
+                    fs.add_file(current, name, size)?;
+                }
+            }
+        }
+        Ok(fs)
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_dir(&self, id: NodeId) -> bool {
+        matches!(self.nodes[id].kind, NodeKind::Dir { .. })
+    }
+
+    pub fn children(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let children = match &self.nodes[id].kind {
+            NodeKind::Dir { children, .. } => Some(children.values().copied()),
+            NodeKind::File { .. } => None,
+        };
+        children.into_iter().flatten()
+    }
+
+    /// Looks a node up by its full path, such as `/a/e`.
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        path.split('/')
+            .filter(|part| !part.is_empty())
+            .try_fold(ROOT, |current, part| match &self.nodes[current].kind {
+                NodeKind::Dir { children, .. } => children.get(part).copied(),
+                NodeKind::File { .. } => None,
+            })
+    }
+
+    pub fn path(&self, id: NodeId) -> String {
+        match self.nodes[id].parent {
+            None => "/".to_owned(),
+            Some(parent) => self.path_to(parent, &self.nodes[id].name),
+        }
+    }
+
+    fn path_to(&self, dir: NodeId, name: &str) -> String {
+        let dir_path = self.path(dir);
+        if dir_path.ends_with('/') {
+            format!("{}{}", dir_path, name)
+        } else {
+            format!("{}/{}", dir_path, name)
+        }
+    }
+
+    /// Directories which appear in the transcript but whose content was never listed.
+    pub fn unlisted_dirs(&self) -> Vec<NodeId> {
+        (0..self.nodes.len())
+            .filter(|id| matches!(self.nodes[*id].kind, NodeKind::Dir { listed: false, .. }))
+            .collect()
+    }
+
+    fn mark_listed(&mut self, id: NodeId) {
+        if let NodeKind::Dir { listed, .. } = &mut self.nodes[id].kind {
+            *listed = true;
+        }
+    }
+
+    fn add_child(&mut self, parent: NodeId, name: &str, kind: NodeKind) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(Node { name: name.to_owned(), parent: Some(parent), kind });
+        if let NodeKind::Dir { children, .. } = &mut self.nodes[parent].kind {
+            children.insert(name.to_owned(), id);
+        }
+        id
+    }
+
+    /// Returns `None` if `name` is a file.
+    pub fn get_or_create_dir(&mut self, parent: NodeId, name: &str) -> Option<NodeId> {
+        match &self.nodes[parent].kind {
+            NodeKind::File { .. } => None,
+            NodeKind::Dir { children, .. } => match children.get(name) {
+                Some(&id) if self.is_dir(id) => Some(id),
+                Some(_) => None,
+                None => Some(self.add_child(
+                    parent,
+                    name,
+                    NodeKind::Dir { children: BTreeMap::new(), listed: false },
+                )),
+            },
+        }
+    }
+
+    pub fn add_file(&mut self, parent: NodeId, name: &str, size: u64) -> Result<NodeId, String> {
+        let existing = match &self.nodes[parent].kind {
+            NodeKind::File { .. } => return Err(format!("'{}' is not a directory", self.path(parent))),
+            NodeKind::Dir { children, .. } => children.get(name).copied(),
+        };
+        match existing {
+            None => Ok(self.add_child(parent, name, NodeKind::File { size })),
+            Some(id) => match &mut self.nodes[id].kind {
+                NodeKind::File { size: existing_size } => {
+                    *existing_size = size;
+                    Ok(id)
+                }
+                NodeKind::Dir { .. } => Err(format!("'{}' is both a file and a directory", self.path(id))),
+            },
+        }
+    }
+
+    /// Total size of every node, indexed by node id, like `du` would report it.
+    pub fn du(&self) -> Vec<u64> {
+        let mut sizes: Vec<u64> = self.nodes.iter()
+            .map(|node| match node.kind {
+                NodeKind::File { size } => size,
+                NodeKind::Dir { .. } => 0,
+            })
+            .collect();
+        // Children always come after their parent, so a reverse pass sees every child first.
+        for id in (1..self.nodes.len()).rev() {
+            if let Some(parent) = self.nodes[id].parent {
+                sizes[parent] += sizes[id];
+            }
+        }
+        sizes
+    }
+
+    pub fn find(&self, query: &Query) -> Vec<NodeId> {
+        let sizes = self.du();
+        let mut found = Vec::new();
+        let mut stack = vec![(ROOT, 0)];
+        while let Some((id, depth)) = stack.pop() {
+            if query.matches(self, id, depth, sizes[id]) {
+                found.push(id);
+            }
+            if query.max_depth.is_none_or(|max| depth < max) {
+                let mut children: Vec<NodeId> = self.children(id).collect();
+                children.reverse();
+                stack.extend(children.into_iter().map(|child| (child, depth + 1)));
+            }
+        }
+        found
+    }
+
+    /// Renders the tree in the same format as the puzzle description.
+    pub fn tree(&self) -> String {
+        let mut out = String::new();
+        let mut stack = vec![(ROOT, 0)];
+        while let Some((id, depth)) = stack.pop() {
+            let node = &self.nodes[id];
+            let description = match node.kind {
+                NodeKind::Dir { .. } => "dir".to_owned(),
+                NodeKind::File { size } => format!("file, size={}", size),
+            };
+            writeln!(out, "{}- {} ({})", "  ".repeat(depth), node.name, description).unwrap();
+            let mut children: Vec<NodeId> = self.children(id).collect();
+            children.reverse();
+            stack.extend(children.into_iter().map(|child| (child, depth + 1)));
+        }
+        out
+    }
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        FileSystem::new()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum KindFilter {
+    Any,
+    Dir,
+    File,
+}
+
+/// Predicates for `FileSystem::find`; every predicate that is set must match.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Query {
+    pub kind: KindFilter,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub name_contains: Option<String>,
+    pub min_depth: Option<usize>,
+    pub max_depth: Option<usize>,
+}
+
+impl Query {
+    pub fn new() -> Query {
+        Query {
+            kind: KindFilter::Any,
+            min_size: None,
+            max_size: None,
+            name_contains: None,
+            min_depth: None,
+            max_depth: None,
+        }
+    }
+
+    pub fn dirs() -> Query {
+        Query { kind: KindFilter::Dir, ..Query::new() }
+    }
+
+    pub fn files() -> Query {
+        Query { kind: KindFilter::File, ..Query::new() }
+    }
+
+    pub fn min_size(self, size: u64) -> Query {
+        Query { min_size: Some(size), ..self }
+    }
+
+    pub fn max_size(self, size: u64) -> Query {
+        Query { max_size: Some(size), ..self }
+    }
+
+    pub fn name_contains(self, pattern: &str) -> Query {
+        Query { name_contains: Some(pattern.to_owned()), ..self }
+    }
+
+    pub fn min_depth(self, depth: usize) -> Query {
+        Query { min_depth: Some(depth), ..self }
+    }
+
+    pub fn max_depth(self, depth: usize) -> Query {
+        Query { max_depth: Some(depth), ..self }
+    }
+
+    fn matches(&self, fs: &FileSystem, id: NodeId, depth: usize, size: u64) -> bool {
+        let kind_matches = match self.kind {
+            KindFilter::Any => true,
+            KindFilter::Dir => fs.is_dir(id),
+            KindFilter::File => !fs.is_dir(id),
+        };
+        kind_matches
+            && self.min_size.is_none_or(|min| size >= min)
+            && self.max_size.is_none_or(|max| size <= max)
+            && self.name_contains.as_ref().is_none_or(|pattern| fs.node(id).name.contains(pattern.as_str()))
+            && self.min_depth.is_none_or(|min| depth >= min)
+            && self.max_depth.is_none_or(|max| depth <= max)
+    }
+}
+
+impl Default for Query {
+    fn default() -> Self {
+        Query::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TEST_INPUT: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    fn paths(fs: &FileSystem, ids: &[NodeId]) -> Vec<String> {
+        ids.iter().map(|id| fs.path(*id)).collect()
+    }
+
+    #[test]
+    fn test_du() {
+        let fs = FileSystem::from_transcript(TEST_INPUT).unwrap();
+        let sizes = fs.du();
+        assert_eq!(sizes[ROOT], 48381165);
+        assert_eq!(sizes[fs.lookup("/a").unwrap()], 94853);
+        assert_eq!(sizes[fs.lookup("/a/e").unwrap()], 584);
+        assert_eq!(sizes[fs.lookup("/d").unwrap()], 24933642);
+        assert_eq!(fs.lookup("/a/x"), None);
+    }
+
+    #[test]
+    fn test_find() {
+        let fs = FileSystem::from_transcript(TEST_INPUT).unwrap();
+        let small_dirs = fs.find(&Query::dirs().max_size(100000));
+        assert_eq!(paths(&fs, &small_dirs), vec!["/a", "/a/e"]);
+        let top_files = fs.find(&Query::files().max_depth(1).min_size(10000000));
+        assert_eq!(paths(&fs, &top_files), vec!["/b.txt"]);
+        let named = fs.find(&Query::new().name_contains("d.").min_depth(2));
+        assert_eq!(paths(&fs, &named), vec!["/d/d.ext", "/d/d.log"]);
+    }
+
+    #[test]
+    fn test_tree() {
+        let fs = FileSystem::from_transcript(TEST_INPUT).unwrap();
+        let expected = "- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+";
+        assert_eq!(fs.tree(), expected);
+    }
+
+    #[test]
+    fn test_unlisted_dirs() {
+        let fs = FileSystem::from_transcript("$ cd /
+$ cd x
+$ cd y
+$ ls
+12 z
+$ cd ..
+$ cd ..
+$ cd ..").unwrap();
+        assert_eq!(fs.du()[ROOT], 12);
+        assert_eq!(paths(&fs, &fs.unlisted_dirs()), vec!["/", "/x"]);
+        assert_eq!(fs.path(fs.lookup("/x/y/z").unwrap()), "/x/y/z");
+    }
+
+    #[test]
+    fn test_cd_into_file() {
+        assert!(FileSystem::from_transcript("$ ls\n12 z\n$ cd z").is_err());
+    }
+}
@@ -1,126 +1,110 @@
-use common::itertools::Itertools;
-use maplit::hashmap;
-use std::collections::HashMap;
 use std::error::Error;
-use std::iter::once;
+use std::path::Path;
+use crate::cleanup::plan_cleanup;
+use crate::fs::{FileSystem, Query, ROOT};
+use crate::replay::{materialize_in_temp_dir, transcript_from_dir};
+
+mod cleanup;
+mod fs;
+mod replay;
 
 static INPUT: &str = include_str!("input");
+static USAGE: &str = "Usage: day_7 [cleanup | tree | materialize | find [files | dirs] [<filter>=<value>...] | du <path>] [--dir <directory>]";
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let fs_tree = build_fs_tree(INPUT)?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    // A real directory can be analysed instead of the puzzle input.
+    let transcript = match args.iter().position(|arg| *arg == "--dir") {
+        None => INPUT.to_owned(),
+        Some(idx) => {
+            let transcript = transcript_from_dir(Path::new(args.get(idx + 1).ok_or(USAGE)?))?;
+            args.drain(idx..=idx + 1);
+            transcript
+        }
+    };
+    let fs = FileSystem::from_transcript(&transcript)?;
 
-    println!("Part 1 result: {}", part_1(&fs_tree));
-    println!("Part 2 result: {}", part_2(&fs_tree));
+    match args.as_slice() {
+        [] => {
+            println!("Part 1 result: {}", part_1(&fs));
+            println!("Part 2 result: {}", part_2(&fs));
+        }
+        ["cleanup"] => print!("{}", plan_cleanup(&fs, 70000000, 30000000)?.explain()),
+        ["tree"] => {
+            print!("{}", fs.tree());
+            for id in fs.unlisted_dirs() {
+                println!("Never listed: {}", fs.path(id));
+            }
+        }
+        ["materialize"] => println!("Materialized in {}", materialize_in_temp_dir(&fs)?.display()),
+        ["find", filters @ ..] => {
+            let sizes = fs.du();
+            for id in fs.find(&parse_query(filters)?) {
+                println!("{}\t{}", sizes[id], fs.path(id));
+            }
+        }
+        ["du", path] => {
+            let id = fs.lookup(path).ok_or(format!("'{}' not found", path))?;
+            println!("{}\t{}", fs.du()[id], fs.path(id));
+        }
+        _ => Err(USAGE)?,
+    }
 
     Ok(())
 }
 
-#[derive(Debug, PartialEq)]
-enum FsNode {
-    Dir(HashMap<String, FsNode>),
-    File(u64),
-}
-
-fn part_1(fs_tree: &HashMap<String, FsNode>) -> u64 {
-    let (_, all_dirs) = size_of_dir(&fs_tree);
-    all_dirs.into_iter().filter(|d| *d < 100000).sum::<u64>()
+/// Reads `find` filters such as `files name=.log min-size=1000 max-depth=2`.
+fn parse_query(filters: &[&str]) -> Result<Query, String> {
+    let (mut query, filters) = match filters {
+        ["files", filters @ ..] => (Query::files(), filters),
+        ["dirs", filters @ ..] => (Query::dirs(), filters),
+        filters => (Query::new(), filters),
+    };
+    for filter in filters {
+        let (key, value) = filter.split_once('=').ok_or(format!("Invalid filter '{}'", filter))?;
+        let invalid = || format!("Invalid value for {}: '{}'", key, value);
+        query = match key {
+            "name" => query.name_contains(value),
+            "min-size" => query.min_size(value.parse().map_err(|_| invalid())?),
+            "max-size" => query.max_size(value.parse().map_err(|_| invalid())?),
+            "min-depth" => query.min_depth(value.parse().map_err(|_| invalid())?),
+            "max-depth" => query.max_depth(value.parse().map_err(|_| invalid())?),
+            _ => return Err(format!("Unknown filter '{}'", key)),
+        };
+    }
+    Ok(query)
 }
 
-fn part_2(fs_tree: &HashMap<String, FsNode>) -> u64 {
-    let (total, all_dirs) = size_of_dir(&fs_tree);
-    let free = 70000000 - total;
-    let must_be_freed = 30000000 - free;
-    all_dirs
+fn part_1(fs: &FileSystem) -> u64 {
+    let sizes = fs.du();
+    fs.find(&Query::dirs().min_depth(1).max_size(99999))
         .into_iter()
-        .filter(|d| *d >= must_be_freed)
-        .sorted()
-        .next()
-        .unwrap()
+        .map(|id| sizes[id])
+        .sum()
 }
 
-fn size_of_dir(dir: &HashMap<String, FsNode>) -> (u64, Vec<u64>) {
-    let mut dirs: Vec<(u64, Vec<u64>)> = vec![];
-    let mut files: Vec<u64> = vec![];
-    for n in dir.values() {
-        match n {
-            FsNode::Dir(inside_node) => dirs.push(size_of_dir(inside_node)),
-            FsNode::File(s) => files.push(*s),
-        }
+fn part_2(fs: &FileSystem) -> u64 {
+    let sizes = fs.du();
+    let total = sizes[ROOT];
+    // Real directories may be larger than the disk, or already leave enough free space.
+    let free = 70000000u64.saturating_sub(total);
+    let must_be_freed = 30000000u64.saturating_sub(free);
+    if must_be_freed == 0 {
+        return 0;
     }
-    let curr_dir_size =
-        files.into_iter().sum::<u64>() + dirs.iter().map(|(size, _)| size).sum::<u64>();
-    let inner_dirs = dirs
+    fs.find(&Query::dirs().min_depth(1).min_size(must_be_freed))
         .into_iter()
-        .flat_map(|(d, inners)| once(d).chain(inners.into_iter()))
-        .collect_vec();
-    (curr_dir_size, inner_dirs)
-}
-
-fn build_fs_tree(input: &str) -> Result<HashMap<String, FsNode>, String> {
-    let mut current_path: Vec<String> = vec![];
-    let mut fs: HashMap<String, FsNode> = HashMap::new();
-
-    for line in input.lines() {
-        if line.starts_with("$ ls") {
-            // Do nothing
-        } else if line.starts_with("$ cd ") {
-            match line.split_at(5).1.trim() {
-                "/" => current_path = vec![],
-                ".." => {
-                    current_path.pop();
-                }
-                path => current_path.push(path.to_owned()),
-            }
-        } else if line.starts_with("$") {
-            Err(format!("Invalid: '{}'", line))?;
-        } else {
-            let mut parts = line.split(" ");
-            let res_type = parts.next().ok_or(format!("Invalid: '{}'", line))?;
-            let name = parts
-                .next()
-                .ok_or(format!("Invalid: '{}'", line))?
-                .to_owned();
-            if res_type == "dir" {
-                add_node(
-                    &mut fs,
-                    &current_path,
-                    name.clone(),
-                    FsNode::Dir(HashMap::new()),
-                );
-            } else {
-                let size = res_type
-                    .parse::<u64>()
-                    .map_err(|_| format!("Invalid: '{}'", line))?;
-                add_node(&mut fs, &current_path, name.clone(), FsNode::File(size));
-            }
-        }
-    }
-    Ok(fs)
-}
-
-fn add_node(fs: &mut HashMap<String, FsNode>, path: &[String], node_name: String, node: FsNode) {
-    let mut curr_dir = fs;
-    for p in path {
-        match curr_dir.get_mut(p) {
-            None => {
-                panic!("Directory not yet visited");
-            }
-            Some(FsNode::Dir(d)) => {
-                curr_dir = d;
-            }
-            Some(FsNode::File(_)) => {
-                panic!("Cannot have a file in path");
-            }
-        }
-    }
-    curr_dir.insert(node_name, node);
+        .map(|id| sizes[id])
+        .min()
+        .unwrap_or(total)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn fs_tree() -> HashMap<String, FsNode> {
+    fn fs() -> FileSystem {
         let test_input = "$ cd /
 $ ls
 dir a
@@ -144,39 +128,26 @@ $ ls
 8033020 d.log
 5626152 d.ext
 7214296 k";
-        build_fs_tree(test_input).unwrap()
+        FileSystem::from_transcript(test_input).unwrap()
     }
 
     #[test]
-    fn test_build_fs_tree() {
-        let expected: HashMap<String, FsNode> = hashmap! {
-            "a".to_owned() => FsNode::Dir(hashmap! {
-                "e".to_owned() => FsNode::Dir(hashmap! {
-                    "i".to_owned() => FsNode::File(584)
-                }),
-                "f".to_owned() => FsNode::File(29116),
-                "g".to_owned() => FsNode::File(2557),
-                "h.lst".to_owned() => FsNode::File(62596),
-            }),
-            "b.txt".to_owned() => FsNode::File(14848514),
-            "c.dat".to_owned() => FsNode::File(8504156),
-            "d".to_owned() => FsNode::Dir(hashmap! {
-                "j".to_owned() => FsNode::File(4060174),
-                "d.log".to_owned() => FsNode::File(8033020),
-                "d.ext".to_owned() => FsNode::File(5626152),
-                "k".to_owned() => FsNode::File(7214296),
-            })
-        };
-        assert_eq!(fs_tree(), expected);
+    fn test_part_1() {
+        assert_eq!(part_1(&fs()), 95437);
     }
 
     #[test]
-    fn test_part_1() {
-        assert_eq!(part_1(&fs_tree()), 95437);
+    fn test_part_2() {
+        assert_eq!(part_2(&fs()), 24933642);
     }
 
     #[test]
-    fn test_part_2() {
-        assert_eq!(part_2(&fs_tree()), 24933642);
+    fn test_parse_query() {
+        let query = parse_query(&["files", "name=.l", "max-depth=2"]).unwrap();
+        assert_eq!(query, Query::files().name_contains(".l").max_depth(2));
+        assert_eq!(parse_query(&["min-size=10"]).unwrap(), Query::new().min_size(10));
+        assert!(parse_query(&["files", "min-size=big"]).is_err());
+        assert!(parse_query(&["size=10"]).is_err());
+        assert!(parse_query(&["dirs", "name"]).is_err());
     }
 }
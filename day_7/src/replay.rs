@@ -0,0 +1,156 @@
+use std::fs::{self, File};
+use std::io::{self, ErrorKind};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::fs::{FileSystem, NodeId, NodeKind, ROOT};
+
+/// Creates the tree under `root`, with sparse placeholder files of the right sizes. Nothing is
+/// created if a name could resolve outside of its parent directory.
+pub fn materialize(fs: &FileSystem, root: &Path) -> io::Result<()> {
+    for id in (0..fs.len()).filter(|id| *id != ROOT) {
+        check_name(&fs.node(id).name)?;
+    }
+    materialize_node(fs, ROOT, root)
+}
+
+/// Only accepts names which are a single, plain path component.
+fn check_name(name: &str) -> io::Result<()> {
+    let mut components = Path::new(name).components();
+    let plain = !name.contains(['/', '\\'])
+        && matches!((components.next(), components.next()), (Some(Component::Normal(_)), None));
+    if plain {
+        Ok(())
+    } else {
+        Err(io::Error::new(ErrorKind::InvalidInput, format!("Unsafe file name {:?}", name)))
+    }
+}
+
+fn materialize_node(fs: &FileSystem, id: NodeId, path: &Path) -> io::Result<()> {
+    match fs.node(id).kind {
+        NodeKind::Dir { .. } => {
+            fs::create_dir_all(path)?;
+            for child in fs.children(id) {
+                materialize_node(fs, child, &path.join(&fs.node(child).name))?;
+            }
+        }
+        NodeKind::File { size } => File::create(path)?.set_len(size)?,
+    }
+    Ok(())
+}
+
+/// Same as `materialize`, in a new directory under the system's temporary directory.
+pub fn materialize_in_temp_dir(fs: &FileSystem) -> io::Result<PathBuf> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let root = std::env::temp_dir().join(format!("day_7-{}-{}", std::process::id(), nanos));
+    if root.exists() {
+        return Err(io::Error::new(ErrorKind::AlreadyExists, format!("{} already exists", root.display())));
+    }
+    materialize(fs, &root)?;
+    Ok(root)
+}
+
+/// Walks a real directory and emits the terminal session which would have explored it, in the
+/// format read by `FileSystem::from_transcript`. Symbolic links are skipped.
+pub fn transcript_from_dir(root: &Path) -> io::Result<String> {
+    let mut lines = vec!["$ cd /".to_owned()];
+    list_dir(root, &mut lines)?;
+    Ok(lines.join("\n"))
+}
+
+fn list_dir(dir: &Path, lines: &mut Vec<String>) -> io::Result<()> {
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().into_string().map_err(|name| {
+            io::Error::new(ErrorKind::InvalidData, format!("Non UTF-8 file name {:?}", name))
+        })?;
+        if name.contains('\n') || name == ".." || name == "/" {
+            return Err(io::Error::new(ErrorKind::InvalidData, format!("Unsupported file name {:?}", name)));
+        }
+        let metadata = fs::symlink_metadata(entry.path())?;
+        if metadata.is_dir() {
+            dirs.push(name);
+        } else if metadata.is_file() {
+            files.push((name, metadata.len()));
+        }
+    }
+    dirs.sort();
+    files.sort();
+
+    lines.push("$ ls".to_owned());
+    lines.extend(dirs.iter().map(|name| format!("dir {}", name)));
+    lines.extend(files.iter().map(|(name, size)| format!("{} {}", size, name)));
+    for name in dirs {
+        lines.push(format!("$ cd {}", name));
+        list_dir(&dir.join(&name), lines)?;
+        lines.push("$ cd ..".to_owned());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{part_1, part_2};
+
+    static TEST_INPUT: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    #[test]
+    fn test_round_trip() {
+        let fs = FileSystem::from_transcript(TEST_INPUT).unwrap();
+        let root = materialize_in_temp_dir(&fs).unwrap();
+        let transcript = transcript_from_dir(&root);
+        fs::remove_dir_all(&root).unwrap();
+
+        let transcript = transcript.unwrap();
+        assert!(transcript.starts_with("$ cd /\n$ ls\ndir a\ndir d\n14848514 b.txt\n8504156 c.dat\n$ cd a\n"));
+        let replayed = FileSystem::from_transcript(&transcript).unwrap();
+        assert_eq!(replayed.tree(), fs.tree());
+        assert_eq!(part_1(&replayed), 95437);
+        assert_eq!(part_2(&replayed), 24933642);
+    }
+
+    #[test]
+    fn test_names_with_spaces() {
+        let fs = FileSystem::from_transcript("$ cd /\n$ ls\ndir my dir\n$ cd my dir\n$ ls\n12 a file").unwrap();
+        let root = materialize_in_temp_dir(&fs).unwrap();
+        let transcript = transcript_from_dir(&root);
+        fs::remove_dir_all(&root).unwrap();
+        let replayed = FileSystem::from_transcript(&transcript.unwrap()).unwrap();
+        assert_eq!(replayed.tree(), fs.tree());
+    }
+
+    #[test]
+    fn test_unsafe_names() {
+        let root = std::env::temp_dir().join(format!("day_7-unsafe-{}", std::process::id()));
+        for listing in ["12 /etc/passwd", "dir ..", "dir .", "12 a/b", "12 a\\b", "12 "] {
+            let fs = FileSystem::from_transcript(&format!("$ cd /\n$ ls\ndir ok\n{}", listing)).unwrap();
+            let err = materialize(&fs, &root).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput, "{}", listing);
+            assert!(!root.exists());
+        }
+    }
+}
@@ -1,13 +1,38 @@
-use common::itertools::Itertools;
+use common::time_execution;
 use std::error::Error;
+use std::fs;
+use crate::views::{compute_views, generate_forest};
+
+mod report;
+mod views;
 
 static INPUT: &str = include_str!("input");
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
     let trees = parse_input(INPUT)?;
 
-    println!("Part 1 result: {}", part_1(&trees));
-    println!("Part 2 result: {}", part_2(&trees));
+    match args.as_slice() {
+        // `cargo run --release -p day_8 -- bench` times the computation on a large generated forest.
+        ["bench"] => benchmark(5000),
+        ["top", k] => {
+            for spot in compute_views(&trees).top_spots(k.parse()?) {
+                println!("({}, {}): {}", spot.x, spot.y, spot.score);
+            }
+        }
+        ["csv", scores_path, visibility_path] => {
+            let views = compute_views(&trees);
+            fs::write(scores_path, views.scores_csv())?;
+            fs::write(visibility_path, views.visibility_csv())?;
+        }
+        ["heatmap", path] => fs::write(path, compute_views(&trees).heatmap_ppm())?,
+        [] => {
+            println!("Part 1 result: {}", part_1(&trees));
+            println!("Part 2 result: {}", part_2(&trees));
+        }
+        _ => Err("Usage: day_8 [bench | top <k> | csv <scores.csv> <visibility.csv> | heatmap <file.ppm>]")?,
+    }
 
     Ok(())
 }
@@ -19,112 +44,24 @@ struct Forest {
     width: usize,
 }
 
-impl Forest {
-    fn get_tree(&self, x: usize, y: usize) -> u32 {
-        *self.trees.get(y).and_then(|l| l.get(x)).unwrap()
-    }
-    fn get_left_of(&self, x: usize, y: usize) -> Vec<u32> {
-        self.trees.get(y).unwrap().iter().take(x).copied().collect()
-    }
-    fn get_right_of(&self, x: usize, y: usize) -> Vec<u32> {
-        self.trees
-            .get(y)
-            .unwrap()
-            .iter()
-            .skip(x + 1)
-            .copied()
-            .collect()
-    }
-    fn get_top_of(&self, x: usize, y: usize) -> Vec<u32> {
-        self.trees
-            .iter()
-            .take(y)
-            .map(|l| l.get(x).unwrap())
-            .copied()
-            .collect()
-    }
-    fn get_bottom_of(&self, x: usize, y: usize) -> Vec<u32> {
-        self.trees
-            .iter()
-            .skip(y + 1)
-            .map(|l| l.get(x).unwrap())
-            .copied()
-            .collect()
-    }
-}
-
 fn part_1(forest: &Forest) -> usize {
-    let mut count: usize = 0;
-    for y in 0..forest.height {
-        for x in 0..forest.width {
-            if is_visible(forest, x, y) {
-                count += 1;
-            }
-        }
-    }
-    count
-}
-
-fn is_visible(forest: &Forest, x: usize, y: usize) -> bool {
-    let tree = forest.get_tree(x, y);
-
-    let hidden_left = forest
-        .get_left_of(x, y)
-        .into_iter()
-        .find(|t| *t >= tree)
-        .is_some();
-    let hidden_right = forest
-        .get_right_of(x, y)
-        .into_iter()
-        .find(|t| *t >= tree)
-        .is_some();
-    let hidden_top = forest
-        .get_top_of(x, y)
-        .into_iter()
-        .find(|t| *t >= tree)
-        .is_some();
-    let hidden_bottom = forest
-        .get_bottom_of(x, y)
-        .into_iter()
-        .find(|t| *t >= tree)
-        .is_some();
-
-    !(hidden_left && hidden_right && hidden_top && hidden_bottom)
+    let views = compute_views(forest);
+    views.visible.iter().flatten().filter(|visible| **visible).count()
 }
 
 fn part_2(forest: &Forest) -> usize {
-    let mut max = 0;
-    for y in 0..forest.height {
-        for x in 0..forest.width {
-            let score = scenic_score(forest, x, y);
-            if score > max {
-                max = score;
-            }
-        }
-    }
-    max
+    let views = compute_views(forest);
+    views.scores.iter().flatten().copied().max().unwrap_or(0)
 }
 
-fn scenic_score(forest: &Forest, x: usize, y: usize) -> usize {
-    fn count_visible(trees: &[u32], tree: u32) -> usize {
-        match trees.iter().find_position(|t| **t >= tree) {
-            None => trees.len(),
-            Some((idx, _)) => idx + 1,
-        }
-    }
-
-    let tree = forest.get_tree(x, y);
-    let mut left = forest.get_left_of(x, y);
-    left.reverse();
-    let right = forest.get_right_of(x, y);
-    let mut top = forest.get_top_of(x, y);
-    top.reverse();
-    let bottom = forest.get_bottom_of(x, y);
-
-    count_visible(&left, tree)
-        * count_visible(&right, tree)
-        * count_visible(&top, tree)
-        * count_visible(&bottom, tree)
+fn benchmark(size: usize) {
+    let forest = time_execution("Generation", || generate_forest(size, size, 42));
+    let views = time_execution(&format!("Views of {}x{} forest", size, size), || compute_views(&forest));
+    println!(
+        "{} visible trees, best scenic score {}",
+        views.visible.iter().flatten().filter(|visible| **visible).count(),
+        views.scores.iter().flatten().max().unwrap_or(&0)
+    );
 }
 
 fn parse_input(input: &str) -> Result<Forest, String> {
@@ -138,7 +75,7 @@ fn parse_input(input: &str) -> Result<Forest, String> {
         .collect::<Result<Vec<Vec<u32>>, String>>()?;
     let height = trees.len();
     let width = trees
-        .get(0)
+        .first()
         .map(|l| l.len())
         .ok_or("Must contain at least one line")?;
     Ok(Forest { trees, height, width })
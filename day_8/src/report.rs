@@ -0,0 +1,126 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Write;
+use crate::views::Views;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct Spot {
+    pub score: usize,
+    pub x: usize,
+    pub y: usize,
+}
+
+/// Orders spots by score, then by reverse position, so that the smallest key is the worst spot.
+type RankKey = Reverse<(usize, Reverse<(usize, usize)>)>;
+
+impl Views {
+    /// Best `k` treehouse spots, by decreasing scenic score then by position.
+    pub fn top_spots(&self, k: usize) -> Vec<Spot> {
+        // Min-heap on the score, so that the worst of the current best spots is popped first.
+        let mut heap: BinaryHeap<RankKey> = BinaryHeap::with_capacity(k + 1);
+        for (y, row) in self.scores.iter().enumerate() {
+            for (x, score) in row.iter().enumerate() {
+                heap.push(Reverse((*score, Reverse((y, x)))));
+                if heap.len() > k {
+                    heap.pop();
+                }
+            }
+        }
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((score, Reverse((y, x))))| Spot { score, x, y })
+            .collect()
+    }
+
+    pub fn scores_csv(&self) -> String {
+        to_csv(&self.scores, |score| score.to_string())
+    }
+
+    pub fn visibility_csv(&self) -> String {
+        to_csv(&self.visible, |visible| if *visible { "1" } else { "0" }.to_owned())
+    }
+
+    /// Heatmap of the scenic scores as a binary PPM image, one pixel per tree, going from dark blue
+    /// for the lowest scores to yellow for the highest ones. Scores span several orders of
+    /// magnitude, so the colours follow their logarithm. Trees visible from outside the forest get
+    /// a red tint.
+    pub fn heatmap_ppm(&self) -> Vec<u8> {
+        let height = self.scores.len();
+        let width = self.scores.first().map_or(0, |row| row.len());
+        let max = self.scores.iter().flatten().max().copied().unwrap_or(0);
+        let max_log = ((max + 1) as f64).ln().max(f64::MIN_POSITIVE);
+
+        let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        for (row, visible_row) in self.scores.iter().zip(self.visible.iter()) {
+            for (score, visible) in row.iter().zip(visible_row.iter()) {
+                let level = ((*score + 1) as f64).ln() / max_log;
+                let [r, g, b] = gradient(level);
+                let r = if *visible { r.saturating_add(64) } else { r };
+                image.extend_from_slice(&[r, g, b]);
+            }
+        }
+        image
+    }
+}
+
+fn to_csv<T>(matrix: &[Vec<T>], format: impl Fn(&T) -> String) -> String {
+    let mut out = String::new();
+    for row in matrix {
+        writeln!(out, "{}", row.iter().map(&format).collect::<Vec<_>>().join(",")).unwrap();
+    }
+    out
+}
+
+/// Colour for a level between 0 and 1.
+fn gradient(level: f64) -> [u8; 3] {
+    let level = level.clamp(0.0, 1.0);
+    [
+        (255.0 * level) as u8,
+        (255.0 * level * level) as u8,
+        (128.0 * (1.0 - level)) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+    use crate::views::compute_views;
+
+    fn test_views() -> Views {
+        compute_views(&parse_input("30373
+25512
+65332
+33549
+35390").unwrap())
+    }
+
+    #[test]
+    fn test_top_spots() {
+        let spots = test_views().top_spots(3);
+        assert_eq!(
+            spots,
+            vec![
+                Spot { score: 8, x: 2, y: 3 },
+                Spot { score: 6, x: 1, y: 2 },
+                Spot { score: 4, x: 2, y: 1 },
+            ]
+        );
+        assert_eq!(test_views().top_spots(100).len(), 25);
+    }
+
+    #[test]
+    fn test_csv() {
+        let views = test_views();
+        assert!(views.scores_csv().starts_with("0,0,0,0,0\n0,1,4,1,0\n"));
+        assert!(views.visibility_csv().starts_with("1,1,1,1,1\n1,1,1,0,1\n"));
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let image = test_views().heatmap_ppm();
+        let header = b"P6\n5 5\n255\n";
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 5 * 5 * 3);
+    }
+}
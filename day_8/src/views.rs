@@ -0,0 +1,149 @@
+use crate::Forest;
+
+/// Visibility and scenic score of every tree in the forest.
+#[derive(Debug, PartialEq)]
+pub struct Views {
+    pub visible: Vec<Vec<bool>>,
+    pub scores: Vec<Vec<usize>>,
+}
+
+/// Side of the forest from which a sweep starts.
+#[derive(Clone, Copy)]
+enum Direction {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// Computes the views from every tree with one sweep of each row and column in both directions.
+/// Each sweep keeps a stack of trees with decreasing heights: the trees shorter than the current
+/// one can never block the view of the following trees, so every tree is pushed and popped at most
+/// once, and the whole computation is O(width × height).
+pub fn compute_views(forest: &Forest) -> Views {
+    let mut views = Views {
+        visible: vec![vec![false; forest.width]; forest.height],
+        scores: vec![vec![1; forest.width]; forest.height],
+    };
+    let mut line = Vec::with_capacity(forest.width.max(forest.height));
+    let mut stack = Vec::with_capacity(forest.width.max(forest.height));
+    for direction in [Direction::Left, Direction::Right, Direction::Top, Direction::Bottom] {
+        let (lines, length) = match direction {
+            Direction::Left | Direction::Right => (forest.height, forest.width),
+            Direction::Top | Direction::Bottom => (forest.width, forest.height),
+        };
+        for line_idx in 0..lines {
+            let coords = |i: usize| match direction {
+                Direction::Left => (i, line_idx),
+                Direction::Right => (length - 1 - i, line_idx),
+                Direction::Top => (line_idx, i),
+                Direction::Bottom => (line_idx, length - 1 - i),
+            };
+            line.clear();
+            line.extend((0..length).map(|i| {
+                let (x, y) = coords(i);
+                forest.trees[y][x]
+            }));
+            sweep_line(&line, &mut stack, |i, visible, distance| {
+                let (x, y) = coords(i);
+                views.visible[y][x] |= visible;
+                views.scores[y][x] *= distance;
+            });
+        }
+    }
+    views
+}
+
+/// Calls `record` with the index of each tree, whether it is visible from the start of the line,
+/// and how many trees it can see towards the start of the line.
+fn sweep_line(heights: &[u32], stack: &mut Vec<usize>, mut record: impl FnMut(usize, bool, usize)) {
+    stack.clear();
+    for (idx, height) in heights.iter().enumerate() {
+        while stack.last().is_some_and(|top| heights[*top] < *height) {
+            stack.pop();
+        }
+        match stack.last() {
+            None => record(idx, true, idx),
+            Some(blocking) => record(idx, false, idx - blocking),
+        }
+        stack.push(idx);
+    }
+}
+
+/// Pseudo-random forest for benchmarks, generated with a linear congruential generator so that it
+/// is reproducible without extra dependencies.
+pub fn generate_forest(width: usize, height: usize, seed: u64) -> Forest {
+    let mut state = seed;
+    let trees = (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    ((state >> 33) % 10) as u32
+                })
+                .collect()
+        })
+        .collect();
+    Forest { trees, height, width }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+
+    fn lines_of_sight(forest: &Forest, x: usize, y: usize) -> [Vec<u32>; 4] {
+        let row = &forest.trees[y];
+        let column: Vec<u32> = forest.trees.iter().map(|line| line[x]).collect();
+        [
+            row[..x].iter().rev().copied().collect(),
+            row[x + 1..].to_vec(),
+            column[..y].iter().rev().copied().collect(),
+            column[y + 1..].to_vec(),
+        ]
+    }
+
+    fn is_visible(forest: &Forest, x: usize, y: usize) -> bool {
+        let tree = forest.trees[y][x];
+        lines_of_sight(forest, x, y).iter().any(|line| line.iter().all(|t| *t < tree))
+    }
+
+    fn scenic_score(forest: &Forest, x: usize, y: usize) -> usize {
+        let tree = forest.trees[y][x];
+        lines_of_sight(forest, x, y)
+            .iter()
+            .map(|line| match line.iter().position(|t| *t >= tree) {
+                None => line.len(),
+                Some(idx) => idx + 1,
+            })
+            .product()
+    }
+
+    #[test]
+    fn test_compute_views() {
+        let forest = parse_input("30373
+25512
+65332
+33549
+35390").unwrap();
+        let views = compute_views(&forest);
+        assert_eq!(views.visible.iter().flatten().filter(|v| **v).count(), 21);
+        assert_eq!(views.scores[1][2], 4);
+        assert_eq!(views.scores[3][2], 8);
+        assert!(!views.visible[1][3] && views.visible[1][1]);
+    }
+
+    #[test]
+    fn test_matches_naive_implementation() {
+        for seed in 0..5 {
+            let forest = generate_forest(23, 17, seed);
+            let views = compute_views(&forest);
+            for y in 0..forest.height {
+                for x in 0..forest.width {
+                    assert_eq!(views.visible[y][x], is_visible(&forest, x, y));
+                    assert_eq!(views.scores[y][x], scenic_score(&forest, x, y));
+                }
+            }
+        }
+    }
+}
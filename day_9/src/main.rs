@@ -1,13 +1,30 @@
-use std::collections::HashSet;
 use std::error::Error;
+use crate::physics::{Adjacency, Physics};
+use crate::rope::{head_steps, visited, Rope};
+
+mod physics;
+mod rope;
 
 static INPUT: &str = include_str!("input");
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
     let moves = parse_input(INPUT)?;
 
-    println!("Part 1 result: {}", part_1(&moves));
-    println!("Part 2 result: {}", part_2(&moves));
+    match args.as_slice() {
+        [] => {
+            println!("Part 1 result: {}", part_1(&moves)?);
+            println!("Part 2 result: {}", part_2(&moves)?);
+        }
+        ["trail", knot_count, knot, options @ ..] => {
+            let knot_count = knot_count.parse::<usize>().map_err(|_| format!("Invalid knot count {}", knot_count))?;
+            let knot = knot.parse::<usize>().map_err(|_| format!("Invalid knot {}", knot))?;
+            let (physics, list) = parse_trail_options(options)?;
+            print!("{}", trail(&moves, knot_count, knot, physics, list)?);
+        }
+        _ => Err("Usage: day_9 [trail <knot count> <knot> [manhattan] [slack=<n>] [list]]")?,
+    }
 
     Ok(())
 }
@@ -23,6 +40,10 @@ enum Direction {
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
 }
 
 impl Direction {
@@ -32,96 +53,61 @@ impl Direction {
             Direction::Down => (from_x, from_y - 1),
             Direction::Left => (from_x - 1, from_y),
             Direction::Right => (from_x + 1, from_y),
+            Direction::UpLeft => (from_x - 1, from_y + 1),
+            Direction::UpRight => (from_x + 1, from_y + 1),
+            Direction::DownLeft => (from_x - 1, from_y - 1),
+            Direction::DownRight => (from_x + 1, from_y - 1),
         }
     }
 }
 
-fn move_tail((x_tail, y_tail): Pos, (x_head, y_head): Pos) -> Pos {
-    if is_adjacent(x_tail, x_head) && is_adjacent(y_tail, y_head) {
-        (x_tail, y_tail)
-    } else if x_tail == x_head {
-        (x_tail, calculate_move(y_tail, y_head))
-    } else if y_tail == y_head {
-        (calculate_move(x_tail, x_head), y_tail)
-    } else if is_adjacent(x_tail, x_head) {
-        (x_head, calculate_move(y_tail, y_head))
-    } else if is_adjacent(y_tail, y_head) {
-        (calculate_move(x_tail, x_head), y_head)
-    } else {
-        (calculate_move(x_tail, x_head), calculate_move(y_tail, y_head))
-    }
+fn part_1(moves: &[Move]) -> Result<usize, String> {
+    Ok(visited(moves, 2, 1)?.len())
 }
 
-fn is_adjacent(x1: i64, x2: i64) -> bool {
-    let diff = x1 -x2;
-    -1 <= diff && diff <= 1
+fn part_2(moves: &[Move]) -> Result<usize, String> {
+    Ok(visited(moves, 10, 9)?.len())
 }
 
-fn calculate_move(from: i64, target: i64) -> i64 {
-    let distance = target - from;
-    if distance > 0 {
-        from + distance - 1
-    } else if distance < 0 {
-        from + distance + 1
-    } else {
-        from
-    }
-}
-
-fn part_1(moves: &[Move]) -> usize {
-    let mut previous_tail_positions = HashSet::new();
-    previous_tail_positions.insert((0, 0));
-
-    let mut current_head_position: Pos = (0, 0);
-    let mut current_tail_position: Pos = (0, 0);
-
-    for Move(direction, distance) in moves {
-        for _ in 0..*distance {
-            current_head_position = direction.new_pos(current_head_position);
-            current_tail_position = move_tail(current_tail_position, current_head_position);
-            previous_tail_positions.insert(current_tail_position);
-            println!(
-                "head {:?}, tail {:?}",
-                current_head_position, current_tail_position
-            );
+/// Reads the physics of a `trail`, and whether to list its coordinates rather than render it.
+fn parse_trail_options(options: &[&str]) -> Result<(Physics, bool), String> {
+    let mut physics = Physics::default();
+    let mut list = false;
+    for option in options {
+        match option.split_once('=') {
+            None if *option == "chebyshev" => physics.adjacency = Adjacency::Chebyshev,
+            None if *option == "manhattan" => physics.adjacency = Adjacency::Manhattan,
+            None if *option == "list" => list = true,
+            Some(("slack", slack)) => {
+                physics.slack = slack.parse::<i64>().ok()
+                    .filter(|slack| *slack >= 1)
+                    .ok_or(format!("Invalid slack {}", slack))?;
+            }
+            _ => return Err(format!("Unknown option {}", option)),
         }
-        println!();
     }
-    previous_tail_positions.len()
+    Ok((physics, list))
 }
 
-fn part_2(moves: &[Move]) -> usize {
-    let mut previous_tail_positions = HashSet::new();
-    previous_tail_positions.insert((0, 0));
-
-    let mut current_positions = [(0,0); 10];
-    for Move(direction, distance) in moves {
-        for _ in 0..*distance {
-            current_positions[0] = direction.new_pos(current_positions[0]); // move head
-            for i in 1..10 {
-                current_positions[i] = move_tail(current_positions[i], current_positions[i - 1]);
-                previous_tail_positions.insert(current_positions[9]);
-            }
-            println!(
-                "positions {:?}",
-                current_positions
-            );
-        }
+/// Trail of the knot at `knot_index` of a rope with the given physics, as a coordinate list or
+/// rendered like the puzzle does.
+fn trail(moves: &[Move], knot_count: usize, knot_index: usize, physics: Physics, list: bool) -> Result<String, String> {
+    if knot_index >= knot_count {
+        return Err(format!("No knot {} in a rope of {} knots", knot_index, knot_count));
+    }
+    let trails = Rope::new(knot_count, physics).steps(head_steps(moves)).trails();
+    if list {
+        Ok(trails.coordinate_list(knot_index))
+    } else {
+        Ok(trails.render(knot_index))
     }
-    previous_tail_positions.len()
 }
 
-// fn part_1(forest: &Forest) -> usize {
-// }
-//
-// fn part_2(forest: &Forest) -> usize {
-// }
-
 fn parse_input(input: &str) -> Result<Vec<Move>, String> {
     input
         .lines()
         .map(|line| {
-            let mut split = line.split(" ");
+            let mut split = line.split(' ');
             let part_1 = split.next().ok_or(format!("Invalid {}", line))?;
             let distance = split
                 .next()
@@ -133,6 +119,10 @@ fn parse_input(input: &str) -> Result<Vec<Move>, String> {
                 "D" => Ok(Move(Direction::Down, distance)),
                 "L" => Ok(Move(Direction::Left, distance)),
                 "R" => Ok(Move(Direction::Right, distance)),
+                "UL" => Ok(Move(Direction::UpLeft, distance)),
+                "UR" => Ok(Move(Direction::UpRight, distance)),
+                "DL" => Ok(Move(Direction::DownLeft, distance)),
+                "DR" => Ok(Move(Direction::DownRight, distance)),
                 _ => Err(format!("Invalid {}", line)),
             }
         })
@@ -170,13 +160,37 @@ R 2";
         assert_eq!(test_moves(), expected)
     }
 
-    // #[test]
-    // fn test_part_1() {
-    //     assert_eq!(part_1(&test_moves()), 13);
-    // }
+    #[test]
+    fn test_part_1() {
+        assert_eq!(part_1(&test_moves()), Ok(13));
+    }
 
     #[test]
     fn test_part_2() {
-        assert_eq!(part_2(&test_moves()), 1);
+        assert_eq!(part_2(&test_moves()), Ok(1));
+        let larger_moves = parse_input("R 5
+U 8
+L 8
+D 3
+R 17
+D 10
+L 25
+U 20").unwrap();
+        assert_eq!(part_2(&larger_moves), Ok(36));
+    }
+
+    #[test]
+    fn test_trail() {
+        let (physics, list) = parse_trail_options(&["manhattan", "slack=2", "list"]).unwrap();
+        assert_eq!(physics, Physics { adjacency: Adjacency::Manhattan, slack: 2 });
+        assert!(list);
+        assert!(parse_trail_options(&["slack=0"]).is_err());
+        assert!(parse_trail_options(&["euclid"]).is_err());
+
+        let moves = parse_input("UR 3").unwrap();
+        let manhattan = Physics { adjacency: Adjacency::Manhattan, slack: 1 };
+        assert_eq!(trail(&moves, 2, 1, manhattan, true), Ok("0,0\n1,0\n2,1\n3,2\n".to_owned()));
+        assert_eq!(trail(&moves, 2, 1, Physics::default(), false), Ok("..#\n.#.\ns..\n".to_owned()));
+        assert!(trail(&moves, 2, 2, Physics::default(), false).is_err());
     }
 }
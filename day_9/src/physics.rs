@@ -0,0 +1,105 @@
+/// Positions in which the knots can be considered touching.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Adjacency {
+    /// Diagonal neighbours touch, and knots move diagonally to catch up (the puzzle's rule).
+    Chebyshev,
+    /// Only orthogonal neighbours touch, and knots move one axis at a time, as many times as needed.
+    Manhattan,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Physics {
+    pub adjacency: Adjacency,
+    /// How far a knot can be from the previous one before it has to move.
+    pub slack: i64,
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Physics { adjacency: Adjacency::Chebyshev, slack: 1 }
+    }
+}
+
+impl Physics {
+    pub fn distance<const N: usize>(&self, a: [i64; N], b: [i64; N]) -> i64 {
+        let diffs = (0..N).map(|axis| (a[axis] - b[axis]).abs());
+        match self.adjacency {
+            Adjacency::Chebyshev => diffs.max().unwrap_or(0),
+            Adjacency::Manhattan => diffs.sum(),
+        }
+    }
+
+    /// New position of a knot after the previous knot moved to `head`.
+    pub fn follow<const N: usize>(&self, tail: [i64; N], head: [i64; N]) -> [i64; N] {
+        if self.distance(tail, head) <= self.slack {
+            return tail;
+        }
+        let mut new_tail = tail;
+        match self.adjacency {
+            Adjacency::Chebyshev => {
+                for axis in 0..N {
+                    new_tail[axis] += (head[axis] - tail[axis]).signum();
+                }
+            }
+            Adjacency::Manhattan => {
+                // Each unit step shortens the distance by one, so diagonal or 3D head steps, which
+                // can add several units at once, are caught up with too. Ties go to the first axis.
+                while self.distance(new_tail, head) > self.slack {
+                    if let Some(axis) = (0..N).rev().max_by_key(|axis| (head[*axis] - new_tail[*axis]).abs()) {
+                        new_tail[axis] += (head[axis] - new_tail[axis]).signum();
+                    }
+                }
+            }
+        }
+        new_tail
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_physics() {
+        let physics = Physics::default();
+        assert_eq!(physics.follow([0, 0], [1, 1]), [0, 0]);
+        assert_eq!(physics.follow([0, 0], [2, 0]), [1, 0]);
+        assert_eq!(physics.follow([0, 0], [0, -2]), [0, -1]);
+        assert_eq!(physics.follow([0, 0], [2, 1]), [1, 1]);
+        assert_eq!(physics.follow([0, 0], [-1, 2]), [-1, 1]);
+        assert_eq!(physics.follow([0, 0], [2, -2]), [1, -1]);
+    }
+
+    #[test]
+    fn test_manhattan_and_slack() {
+        let manhattan = Physics { adjacency: Adjacency::Manhattan, slack: 1 };
+        assert_eq!(manhattan.follow([0, 0], [1, 1]), [1, 0]);
+        assert_eq!(manhattan.follow([0, 0], [1, 2]), [1, 1]);
+        assert_eq!(manhattan.follow([0, 0], [1, 0]), [0, 0]);
+        assert_eq!(manhattan.follow([0, 0], [2, 1]), [2, 0]);
+        assert_eq!(manhattan.follow([0, 0, 0], [1, 1, 1]), [1, 1, 0]);
+
+        let slack = Physics { adjacency: Adjacency::Chebyshev, slack: 2 };
+        assert_eq!(slack.follow([0, 0], [2, 2]), [0, 0]);
+        assert_eq!(slack.follow([0, 0], [3, 1]), [1, 1]);
+    }
+
+    #[test]
+    fn test_manhattan_diagonal_steps() {
+        let manhattan = Physics { adjacency: Adjacency::Manhattan, slack: 1 };
+        let (mut head, mut tail) = ([0, 0], [0, 0]);
+        for _ in 0..10 {
+            head = [head[0] + 1, head[1] + 1];
+            tail = manhattan.follow(tail, head);
+            assert_eq!(manhattan.distance(tail, head), 1);
+        }
+        assert_eq!(tail, [10, 9]);
+
+        let (mut head, mut tail) = ([0, 0, 0], [0, 0, 0]);
+        for _ in 0..10 {
+            head = [head[0] + 1, head[1] - 1, head[2] + 1];
+            tail = manhattan.follow(tail, head);
+            assert_eq!(manhattan.distance(tail, head), 1);
+        }
+    }
+}
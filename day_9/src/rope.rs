@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use crate::physics::Physics;
+use crate::Move;
+
+/// A rope whose knots move in `N` dimensions. The first knot is the head, and each following knot
+/// follows the previous one according to the rope's physics.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Rope<const N: usize> {
+    knots: Vec<[i64; N]>,
+    physics: Physics,
+}
+
+impl<const N: usize> Rope<N> {
+    /// A rope with all its knots at the origin.
+    pub fn new(knot_count: usize, physics: Physics) -> Rope<N> {
+        Rope { knots: vec![[0; N]; knot_count.max(1)], physics }
+    }
+
+    pub fn knots(&self) -> &[[i64; N]] {
+        &self.knots
+    }
+
+    /// Moves the head by `head_step`, and each following knot after the previous one.
+    pub fn step(&mut self, head_step: [i64; N]) {
+        for (axis, delta) in head_step.iter().enumerate() {
+            self.knots[0][axis] += delta;
+        }
+        for i in 1..self.knots.len() {
+            self.knots[i] = self.physics.follow(self.knots[i], self.knots[i - 1]);
+        }
+    }
+
+    /// Iterates over the states of the rope after each of the head steps.
+    pub fn steps<I: IntoIterator<Item = [i64; N]>>(&self, head_steps: I) -> Steps<N, I::IntoIter> {
+        Steps { rope: self.clone(), head_steps: head_steps.into_iter() }
+    }
+}
+
+pub struct Steps<const N: usize, I> {
+    rope: Rope<N>,
+    head_steps: I,
+}
+
+impl<const N: usize, I: Iterator<Item = [i64; N]>> Steps<N, I> {
+    /// Runs the remaining steps and records every position taken by each knot, starting with the
+    /// current ones.
+    pub fn trails(self) -> Trails<N> {
+        let mut knots: Vec<Vec<[i64; N]>> = self.rope.knots.iter().map(|pos| vec![*pos]).collect();
+        for state in self {
+            for (trail, pos) in knots.iter_mut().zip(state.knots()) {
+                trail.push(*pos);
+            }
+        }
+        Trails { knots }
+    }
+}
+
+impl<const N: usize, I: Iterator<Item = [i64; N]>> Iterator for Steps<N, I> {
+    type Item = Rope<N>;
+
+    fn next(&mut self) -> Option<Rope<N>> {
+        let head_step = self.head_steps.next()?;
+        self.rope.step(head_step);
+        Some(self.rope.clone())
+    }
+}
+
+/// Unit steps of the head for the moves of the puzzle input.
+pub fn head_steps(moves: &[Move]) -> impl Iterator<Item = [i64; 2]> + '_ {
+    moves.iter().flat_map(|Move(direction, distance)| {
+        let (x, y) = direction.new_pos((0, 0));
+        (0..*distance).map(move |_| [x, y])
+    })
+}
+
+/// Positions visited by the knot at `knot_index` (0 being the head), including the starting one,
+/// with the puzzle's physics.
+pub fn visited(moves: &[Move], knot_count: usize, knot_index: usize) -> Result<HashSet<[i64; 2]>, String> {
+    if knot_index >= knot_count {
+        return Err(format!("No knot {} in a rope of {} knots", knot_index, knot_count));
+    }
+    let rope = Rope::new(knot_count, Physics::default());
+    let mut positions: HashSet<[i64; 2]> = rope.steps(head_steps(moves))
+        .map(|state| state.knots()[knot_index])
+        .collect();
+    positions.insert(rope.knots()[knot_index]);
+    Ok(positions)
+}
+
+/// Every position taken by each knot of a rope, in order.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Trails<const N: usize> {
+    pub knots: Vec<Vec<[i64; N]>>,
+}
+
+impl<const N: usize> Trails<N> {
+    pub fn visited(&self, knot: usize) -> HashSet<[i64; N]> {
+        self.knots[knot].iter().copied().collect()
+    }
+
+    /// The trail of one knot as one comma-separated coordinate list per line.
+    pub fn coordinate_list(&self, knot: usize) -> String {
+        let mut out = String::new();
+        for pos in &self.knots[knot] {
+            let coords: Vec<String> = pos.iter().map(|c| c.to_string()).collect();
+            writeln!(out, "{}", coords.join(",")).unwrap();
+        }
+        out
+    }
+}
+
+impl Trails<2> {
+    /// Renders the positions visited by one knot like the puzzle does, with `s` for the start,
+    /// `#` for the visited positions and `y` pointing up.
+    pub fn render(&self, knot: usize) -> String {
+        let visited = self.visited(knot);
+        let min_x = visited.iter().map(|p| p[0]).min().unwrap_or(0);
+        let max_x = visited.iter().map(|p| p[0]).max().unwrap_or(0);
+        let min_y = visited.iter().map(|p| p[1]).min().unwrap_or(0);
+        let max_y = visited.iter().map(|p| p[1]).max().unwrap_or(0);
+        let mut out = String::new();
+        for y in (min_y..=max_y).rev() {
+            for x in min_x..=max_x {
+                out.push(if [x, y] == [0, 0] {
+                    's'
+                } else if visited.contains(&[x, y]) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+    use crate::physics::Adjacency;
+
+    fn test_moves() -> Vec<Move> {
+        parse_input("R 4\nU 4\nL 3\nD 1\nR 4\nD 1\nL 5\nR 2").unwrap()
+    }
+
+    #[test]
+    fn test_steps() {
+        let moves = parse_input("R 4\nU 2").unwrap();
+        let states: Vec<Rope<2>> = Rope::new(3, Physics::default()).steps(head_steps(&moves)).collect();
+        assert_eq!(states.len(), 6);
+        assert_eq!(states[3].knots(), &[[4, 0], [3, 0], [2, 0]]);
+        assert_eq!(states[5].knots(), &[[4, 2], [4, 1], [3, 1]]);
+    }
+
+    #[test]
+    fn test_visited() {
+        let moves = test_moves();
+        assert_eq!(visited(&moves, 2, 1).unwrap().len(), 13);
+        assert_eq!(visited(&moves, 10, 9).unwrap().len(), 1);
+        assert_eq!(visited(&moves, 2, 0).unwrap().len(), visited(&moves, 10, 0).unwrap().len());
+        assert!(visited(&moves, 2, 2).is_err());
+    }
+
+    #[test]
+    fn test_diagonal_moves() {
+        let moves = parse_input("UR 3\nDL 1").unwrap();
+        let states: Vec<Rope<2>> = Rope::new(2, Physics::default()).steps(head_steps(&moves)).collect();
+        assert_eq!(states[2].knots(), &[[3, 3], [2, 2]]);
+        assert_eq!(states[3].knots(), &[[2, 2], [2, 2]]);
+        assert_eq!(visited(&moves, 2, 1).unwrap(), HashSet::from([[0, 0], [1, 1], [2, 2]]));
+    }
+
+    #[test]
+    fn test_trails() {
+        let trails = Rope::new(10, Physics::default()).steps(head_steps(&test_moves())).trails();
+        assert_eq!(trails.visited(1).len(), 13);
+        assert_eq!(trails.visited(9).len(), 1);
+        assert_eq!(trails.knots[0].len(), 25);
+
+        let slack = Physics { adjacency: Adjacency::Chebyshev, slack: 2 };
+        let trails = Rope::new(2, slack).steps(head_steps(&parse_input("R 4").unwrap())).trails();
+        assert_eq!(trails.knots[1], vec![[0, 0], [0, 0], [0, 0], [1, 0], [2, 0]]);
+    }
+
+    #[test]
+    fn test_3d() {
+        let steps = [[0, 0, 1], [0, 0, 1], [1, 0, 0], [0, 1, 0], [0, 1, 0]];
+        let trails = Rope::new(2, Physics::default()).steps(steps).trails();
+        assert_eq!(trails.knots[1], vec![[0, 0, 0], [0, 0, 0], [0, 0, 1], [0, 0, 1], [0, 0, 1], [1, 1, 2]]);
+        assert_eq!(trails.coordinate_list(1).lines().last(), Some("1,1,2"));
+    }
+
+    #[test]
+    fn test_render() {
+        let trails = Rope::new(2, Physics::default()).steps(head_steps(&test_moves())).trails();
+        let expected = "..##.
+...##
+.####
+....#
+s###.
+";
+        assert_eq!(trails.render(1), expected);
+    }
+}